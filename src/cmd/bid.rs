@@ -0,0 +1,642 @@
+use crate::cmd::columns::format_mb;
+use crate::cmd::condor::{projection, JobAd, JobAttr, JobStatus};
+use crate::cmd::job_actions::MAX_BID;
+use crate::cmd::price::percentile;
+use crate::utils::output::{OutputFormat, Records};
+use crate::utils::remote::RemoteExecutor;
+use crate::utils::serde::{deserialize_opt_f64_lenient, deserialize_opt_i64_lenient};
+use crate::utils::ssh::parse_json_relaxed;
+use crate::utils::time::{human_duration, now_unix};
+use comfy_table::{
+    presets::UTF8_FULL, Attribute, Cell, CellAlignment, Color, ContentArrangement, Table,
+};
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::BTreeMap;
+
+// Window over which job starts are counted to estimate throughput
+const START_RATE_WINDOW_SECS: i64 = 24 * 3600;
+
+/// Resources of the job a bid is suggested for.
+#[derive(Debug, Clone)]
+pub struct BidRequest {
+    pub gpus: i64,
+    /// `request_memory` in MB
+    pub memory_mb: Option<i64>,
+    /// Minimum memory per GPU in MB
+    pub gpu_memory_mb: Option<i64>,
+    /// Wait the job should start within
+    pub within_secs: u64,
+}
+
+/// A slot ad from `condor_status -json`.
+#[derive(Deserialize, Debug, Clone)]
+struct SlotAd {
+    #[serde(rename = "Machine")]
+    machine: String,
+    #[serde(rename = "State")]
+    state: Option<String>,
+    /// GPUs and memory of this slot (for partitionable slots: still unclaimed)
+    #[serde(
+        rename = "GPUs",
+        default,
+        deserialize_with = "deserialize_opt_i64_lenient"
+    )]
+    gpus: Option<i64>,
+    #[serde(
+        rename = "Memory",
+        default,
+        deserialize_with = "deserialize_opt_i64_lenient"
+    )]
+    memory: Option<i64>,
+    #[serde(
+        rename = "TotalGPUs",
+        default,
+        deserialize_with = "deserialize_opt_i64_lenient"
+    )]
+    total_gpus: Option<i64>,
+    #[serde(
+        rename = "TotalMemory",
+        default,
+        deserialize_with = "deserialize_opt_i64_lenient"
+    )]
+    total_memory: Option<i64>,
+    #[serde(
+        rename = "CUDAGlobalMemoryMb",
+        default,
+        deserialize_with = "deserialize_opt_f64_lenient"
+    )]
+    cuda_global_memory_mb: Option<f64>,
+    #[serde(
+        rename = "GPUs_GlobalMemoryMb",
+        default,
+        deserialize_with = "deserialize_opt_f64_lenient"
+    )]
+    gpus_global_memory_mb: Option<f64>,
+}
+
+const SLOT_ATTRS: &str =
+    "Machine,State,GPUs,Memory,TotalGPUs,TotalMemory,CUDAGlobalMemoryMb,GPUs_GlobalMemoryMb";
+
+/// A machine with the resources of all its slots.
+#[derive(Debug, Default)]
+struct Machine {
+    total_gpus: i64,
+    total_memory: i64,
+    gpu_memory_mb: Option<f64>,
+    /// Largest unclaimed share of GPUs and memory on one slot
+    free_gpus: i64,
+    free_memory: i64,
+}
+
+fn machines(slots: &[SlotAd]) -> BTreeMap<String, Machine> {
+    let mut machines: BTreeMap<String, Machine> = BTreeMap::new();
+    for slot in slots {
+        let m = machines.entry(slot.machine.clone()).or_default();
+        m.total_gpus = m.total_gpus.max(slot.total_gpus.unwrap_or(0));
+        m.total_memory = m.total_memory.max(slot.total_memory.unwrap_or(0));
+        if let Some(mb) = slot.cuda_global_memory_mb.or(slot.gpus_global_memory_mb) {
+            m.gpu_memory_mb = Some(m.gpu_memory_mb.unwrap_or(0.0).max(mb));
+        }
+        if slot.state.as_deref() == Some("Unclaimed") {
+            let gpus = slot.gpus.unwrap_or(0);
+            if gpus > m.free_gpus {
+                m.free_gpus = gpus;
+                m.free_memory = slot.memory.unwrap_or(0);
+            }
+        }
+    }
+    machines
+}
+
+impl Machine {
+    fn fits(&self, request: &BidRequest) -> bool {
+        self.total_gpus >= request.gpus
+            && request.memory_mb.is_none_or(|mb| self.total_memory >= mb)
+            && request
+                .gpu_memory_mb
+                .is_none_or(|mb| self.gpu_memory_mb.is_some_and(|have| have >= mb as f64))
+    }
+
+    fn fits_now(&self, request: &BidRequest) -> bool {
+        self.free_gpus >= request.gpus && request.memory_mb.is_none_or(|mb| self.free_memory >= mb)
+    }
+}
+
+// `slot1_1@gpu-node-3.cluster` -> `gpu-node-3.cluster`
+fn host_machine(remote_host: &str) -> &str {
+    remote_host
+        .rsplit_once('@')
+        .map_or(remote_host, |(_, machine)| machine)
+}
+
+/// What the queue and the pool say about the machines a job could run on.
+#[derive(Debug, Default)]
+struct Market {
+    matching_machines: usize,
+    matching_gpus: i64,
+    /// GPUs that could be claimed right now, on machines with room for the whole job
+    free_gpus: i64,
+    /// Bids and GPU counts of idle jobs competing for the same machines
+    idle: Vec<(f64, i64)>,
+    /// Bids of the GPU jobs running on those machines
+    running: Vec<f64>,
+    /// `MachineAttrMinRunningPrice0` of those jobs: the lowest bid running on the
+    /// machine when they were matched
+    min_running_prices: Vec<f64>,
+    /// GPUs started per hour on those machines, counting jobs still running
+    start_rate: f64,
+}
+
+impl Market {
+    /// GPUs requested by idle jobs that would be matched before a job bidding `bid`.
+    /// Equal bids count as ahead since they were queued earlier.
+    fn gpus_ahead(&self, bid: f64) -> i64 {
+        self.idle
+            .iter()
+            .filter(|(price, _)| *price >= bid)
+            .map(|(_, gpus)| gpus)
+            .sum()
+    }
+
+    /// Typical lowest running bid a new job had to beat, if any was recorded.
+    fn floor(&self) -> Option<f64> {
+        let mut sorted = self.min_running_prices.clone();
+        if sorted.is_empty() {
+            return None;
+        }
+        sorted.sort_by(f64::total_cmp);
+        Some(percentile(&sorted, 0.5))
+    }
+
+    /// Seconds until a job of `gpus` GPUs bidding `bid` starts, assuming freed
+    /// GPUs go to the highest bids first. `None` if no starts were observed.
+    fn estimated_wait(&self, bid: f64, gpus: i64) -> Option<f64> {
+        let needed = self.gpus_ahead(bid) + gpus - self.free_gpus;
+        if needed <= 0 {
+            return Some(0.0);
+        }
+        (self.start_rate > 0.0).then(|| needed as f64 / self.start_rate * 3600.0)
+    }
+}
+
+fn build_market(jobs: &[JobAd], slots: &[SlotAd], request: &BidRequest, now: i64) -> Market {
+    let machines = machines(slots);
+    let matching: BTreeMap<&String, &Machine> =
+        machines.iter().filter(|(_, m)| m.fits(request)).collect();
+    // Jobs demanding more GPU memory than the matching machines have compete elsewhere
+    let max_gpu_memory = matching
+        .values()
+        .filter_map(|m| m.gpu_memory_mb)
+        .reduce(f64::max);
+    let competes = |job: &JobAd| {
+        job.gpus() > 0
+            && match (job.gpu_memory_requirement(), max_gpu_memory) {
+                (Some(needed), Some(have)) => needed as f64 <= have,
+                _ => true,
+            }
+    };
+    let on_matching = |job: &JobAd| {
+        job.remote_host
+            .as_deref()
+            .is_some_and(|h| matching.contains_key(&host_machine(h).to_string()))
+    };
+    let running: Vec<&JobAd> = jobs
+        .iter()
+        .filter(|j| j.status() == JobStatus::Running && j.gpus() > 0 && on_matching(j))
+        .collect();
+    let started_recently: i64 = running
+        .iter()
+        .filter(|j| {
+            j.job_current_start_date
+                .is_some_and(|t| now - t <= START_RATE_WINDOW_SECS)
+        })
+        .map(|j| j.gpus())
+        .sum();
+    Market {
+        matching_machines: matching.len(),
+        matching_gpus: matching.values().map(|m| m.total_gpus).sum(),
+        free_gpus: matching
+            .values()
+            .filter(|m| m.fits_now(request))
+            .map(|m| m.free_gpus)
+            .sum(),
+        idle: jobs
+            .iter()
+            .filter(|j| j.status() == JobStatus::Idle && competes(j))
+            .map(|j| (j.price(), j.gpus()))
+            .collect(),
+        running: running.iter().map(|j| j.price()).collect(),
+        min_running_prices: running
+            .iter()
+            .filter_map(|j| j.machine_attr_min_running_price0)
+            .collect(),
+        start_rate: started_recently as f64 * 3600.0 / START_RATE_WINDOW_SECS as f64,
+    }
+}
+
+/// A suggested bid with the reasoning behind it.
+#[derive(Debug, PartialEq)]
+struct Suggestion {
+    bid: f64,
+    wait_secs: Option<f64>,
+    reason: String,
+}
+
+fn format_wait(secs: Option<f64>) -> String {
+    match secs {
+        Some(s) if s <= 0.0 => "now".to_string(),
+        Some(s) => format!("~{}", human_duration(s.round() as u64)),
+        None => "unknown".to_string(),
+    }
+}
+
+/// Bids worth considering: the floor and just above each idle competitor's bid.
+fn candidate_bids(market: &Market) -> Vec<f64> {
+    // Clamped so the list is never empty, even if the floor exceeds the bid range
+    let floor = market.floor().unwrap_or(0.0).ceil().min(MAX_BID);
+    let mut bids: Vec<f64> = std::iter::once(floor)
+        .chain(market.idle.iter().map(|(price, _)| price + 1.0))
+        .filter(|b| *b >= floor && *b <= MAX_BID)
+        .collect();
+    bids.sort_by(f64::total_cmp);
+    bids.dedup();
+    bids
+}
+
+fn suggest(market: &Market, request: &BidRequest) -> Suggestion {
+    let target = request.within_secs as f64;
+    let candidates = candidate_bids(market);
+    let lowest = candidates[0];
+    if let Some(bid) = candidates.iter().copied().find(|b| {
+        market
+            .estimated_wait(*b, request.gpus)
+            .is_some_and(|w| w <= target)
+    }) {
+        let wait = market.estimated_wait(bid, request.gpus);
+        let ahead = market.gpus_ahead(bid);
+        let reason = if wait == Some(0.0) {
+            format!(
+                "{} free on matching machines and idle jobs bidding {} or more want only {} of them, so the job should start at the next negotiation cycle.",
+                gpus_label(market.free_gpus),
+                bid,
+                ahead
+            )
+        } else if bid == lowest {
+            format!(
+                "Idle jobs bidding {} or more want {}; at ~{:.1} GPUs/hour freed on matching machines the job should start in {}, within the target of {}. Bidding less than the typical lowest running bid is not recommended.",
+                bid,
+                gpus_label(ahead),
+                market.start_rate,
+                format_wait(wait),
+                human_duration(request.within_secs)
+            )
+        } else {
+            format!(
+                "This is the lowest bid that stays ahead of enough idle jobs: those bidding {} or more want {}, which at ~{:.1} GPUs/hour freed on matching machines means a start in {}, within the target of {}.",
+                bid,
+                gpus_label(ahead),
+                market.start_rate,
+                format_wait(wait),
+                human_duration(request.within_secs)
+            )
+        };
+        return Suggestion {
+            bid,
+            wait_secs: wait,
+            reason,
+        };
+    }
+
+    if market.start_rate <= 0.0 {
+        // No throughput estimate: stay above most running jobs instead
+        let mut running = market.running.clone();
+        running.sort_by(f64::total_cmp);
+        let bid = if running.is_empty() {
+            lowest
+        } else {
+            percentile(&running, 0.75).ceil().max(lowest)
+        };
+        return Suggestion {
+            bid,
+            wait_secs: None,
+            reason: format!(
+                "No job started on matching machines in the last {} and none are free, so the wait cannot be estimated. {} outbids three quarters of the running GPU jobs.",
+                human_duration(START_RATE_WINDOW_SECS as u64),
+                bid
+            ),
+        };
+    }
+
+    // Even the front of the queue misses the target: bid for the front
+    let bid = *candidates.last().unwrap_or(&lowest);
+    let wait = market.estimated_wait(bid, request.gpus);
+    Suggestion {
+        bid,
+        wait_secs: wait,
+        reason: format!(
+            "Even ahead of every idle competitor, the job needs {} more than are free and ~{:.1} GPUs/hour are freed, so it is unlikely to start within {}. {} puts it at the front of the queue (estimated start in {}); consider fewer GPUs or a longer wait.",
+            gpus_label(request.gpus - market.free_gpus.min(request.gpus)),
+            market.start_rate,
+            human_duration(request.within_secs),
+            bid,
+            format_wait(wait)
+        ),
+    }
+}
+
+fn gpus_label(gpus: i64) -> String {
+    format!("{} GPU{}", gpus, if gpus == 1 { "" } else { "s" })
+}
+
+fn describe_request(request: &BidRequest) -> String {
+    let mut parts = vec![gpus_label(request.gpus)];
+    if let Some(mb) = request.memory_mb {
+        parts.push(format!("{} memory", format_mb(mb)));
+    }
+    if let Some(mb) = request.gpu_memory_mb {
+        parts.push(format!(">= {} per GPU", format_mb(mb)));
+    }
+    parts.join(", ")
+}
+
+fn range(prices: &[f64]) -> String {
+    let min = prices.iter().copied().reduce(f64::min).unwrap_or(0.0);
+    let max = prices.iter().copied().reduce(f64::max).unwrap_or(0.0);
+    format!("{}-{}", min, max)
+}
+
+fn candidates_table(market: &Market, request: &BidRequest, suggestion: &Suggestion) -> Table {
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("Bid")
+                .add_attribute(Attribute::Bold)
+                .set_alignment(CellAlignment::Right),
+            Cell::new("GPUs Queued Ahead")
+                .add_attribute(Attribute::Bold)
+                .set_alignment(CellAlignment::Right),
+            Cell::new("Estimated Start")
+                .add_attribute(Attribute::Bold)
+                .set_alignment(CellAlignment::Right),
+        ]);
+    // A few points around the suggestion to show the trade-off
+    let candidates = candidate_bids(market);
+    let at = candidates
+        .iter()
+        .position(|b| *b == suggestion.bid)
+        .unwrap_or(0);
+    let mut shown: Vec<f64> = candidates
+        .iter()
+        .skip(at.saturating_sub(2))
+        .take(5)
+        .copied()
+        .collect();
+    if !shown.contains(&suggestion.bid) {
+        shown.push(suggestion.bid);
+    }
+    for bid in shown {
+        let color = if bid == suggestion.bid {
+            Color::Green
+        } else {
+            Color::Reset
+        };
+        table.add_row(vec![
+            Cell::new(bid).fg(color).set_alignment(CellAlignment::Right),
+            Cell::new(market.gpus_ahead(bid)).set_alignment(CellAlignment::Right),
+            Cell::new(format_wait(market.estimated_wait(bid, request.gpus)))
+                .fg(color)
+                .set_alignment(CellAlignment::Right),
+        ]);
+    }
+    table
+}
+
+fn suggestion_records(market: &Market, suggestion: &Suggestion) -> Records {
+    let mut records = Records::new(vec![
+        "suggested_bid",
+        "estimated_wait_secs",
+        "matching_machines",
+        "matching_gpus",
+        "free_gpus",
+        "idle_gpus_ahead",
+        "typical_min_running_price",
+        "start_rate_gpus_per_hour",
+        "reason",
+    ]);
+    records.push(vec![
+        json!(suggestion.bid),
+        json!(suggestion.wait_secs.map(|w| w.round() as i64)),
+        json!(market.matching_machines),
+        json!(market.matching_gpus),
+        json!(market.free_gpus),
+        json!(market.gpus_ahead(suggestion.bid)),
+        json!(market.floor()),
+        json!(market.start_rate),
+        json!(suggestion.reason),
+    ]);
+    records
+}
+
+pub fn handle_bid_suggest(
+    exec: &dyn RemoteExecutor,
+    request: &BidRequest,
+    output: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if request.gpus < 1 {
+        return Err("--gpus must be at least 1".into());
+    }
+    if output.is_table() {
+        println!("Connecting to cluster and fetching queue and machine data...");
+    }
+
+    let attrs = projection(&[
+        JobAttr::JobPrio,
+        JobAttr::JobStatus,
+        JobAttr::RequestGpus,
+        JobAttr::Requirements,
+        JobAttr::RemoteHost,
+        JobAttr::JobCurrentStartDate,
+        JobAttr::MachineAttrMinRunningPrice0,
+    ]);
+    let out = exec.run(&format!("condor_q -json -attributes {}", attrs))?;
+    if !out.success() {
+        let stderr = String::from_utf8_lossy(&out.stderr);
+        return Err(format!("SSH command failed: {}", stderr).into());
+    }
+    let jobs: Vec<JobAd> = parse_json_relaxed(&out.stdout)
+        .map_err(|e| format!("Failed to parse JSON response: {}", e))?;
+
+    let out = exec.run(&format!("condor_status -json -attributes {}", SLOT_ATTRS))?;
+    if !out.success() {
+        let stderr = String::from_utf8_lossy(&out.stderr);
+        return Err(format!("condor_status failed: {}", stderr).into());
+    }
+    let slots: Vec<SlotAd> = parse_json_relaxed(&out.stdout)
+        .map_err(|e| format!("Failed to parse condor_status output: {}", e))?;
+
+    let market = build_market(&jobs, &slots, request, now_unix());
+    if market.matching_machines == 0 {
+        return Err(format!(
+            "No machine in the pool has {}; check the requested resources",
+            describe_request(request)
+        )
+        .into());
+    }
+    let suggestion = suggest(&market, request);
+
+    if !output.is_table() {
+        print!(
+            "{}",
+            suggestion_records(&market, &suggestion).render(output)
+        );
+        return Ok(());
+    }
+
+    println!(
+        "\nBid suggestion for {}, starting within {}:\n",
+        describe_request(request),
+        human_duration(request.within_secs)
+    );
+    println!(
+        "  Matching machines: {} with {} GPUs; {} GPUs free now on machines with room for the job",
+        market.matching_machines, market.matching_gpus, market.free_gpus
+    );
+    if market.running.is_empty() {
+        println!("  Running GPU jobs on them: none");
+    } else {
+        println!(
+            "  Running GPU jobs on them: {}, bidding {}",
+            market.running.len(),
+            range(&market.running)
+        );
+    }
+    match market.floor() {
+        Some(floor) => println!(
+            "  Lowest running bid when those jobs were matched (MachineAttrMinRunningPrice0): median {}",
+            floor
+        ),
+        None => println!("  Lowest running bid at match time: not recorded"),
+    }
+    let idle_prices: Vec<f64> = market.idle.iter().map(|(p, _)| *p).collect();
+    if idle_prices.is_empty() {
+        println!("  Idle GPU jobs competing for them: none");
+    } else {
+        println!(
+            "  Idle GPU jobs competing for them: {} wanting {} GPUs, bidding {}",
+            market.idle.len(),
+            market.gpus_ahead(0.0),
+            range(&idle_prices)
+        );
+    }
+    println!(
+        "  GPUs started in the last {} and still running: ~{:.1} per hour\n",
+        human_duration(START_RATE_WINDOW_SECS as u64),
+        market.start_rate
+    );
+    println!("{}", candidates_table(&market, request, &suggestion));
+    println!("\nSuggested bid: {}", suggestion.bid);
+    println!("{}", suggestion.reason);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::remote::FixtureExecutor;
+
+    fn request(gpus: i64) -> BidRequest {
+        BidRequest {
+            gpus,
+            memory_mb: None,
+            gpu_memory_mb: Some(60000),
+            within_secs: 3600,
+        }
+    }
+
+    fn market(free_gpus: i64, start_rate: f64) -> Market {
+        Market {
+            matching_machines: 2,
+            matching_gpus: 16,
+            free_gpus,
+            idle: vec![(300.0, 4), (200.0, 2), (100.0, 8)],
+            running: vec![150.0, 250.0, 400.0],
+            min_running_prices: vec![50.0, 80.0, 120.0],
+            start_rate,
+        }
+    }
+
+    #[test]
+    fn suggests_lowest_bid_meeting_the_target() {
+        // Starting within 1h at 5 GPUs/hour: at most 4 GPUs may be queued ahead of a 1 GPU job
+        let s = suggest(&market(0, 5.0), &request(1));
+        assert_eq!(s.bid, 201.0);
+        assert_eq!(s.wait_secs, Some(3600.0));
+        // Free GPUs cover the queue ahead: the floor is enough
+        let s = suggest(&market(16, 5.0), &request(2));
+        assert_eq!((s.bid, s.wait_secs), (80.0, Some(0.0)));
+        // The target is out of reach: bid for the front of the queue
+        let s = suggest(&market(0, 1.0), &request(2));
+        assert_eq!(s.bid, 301.0);
+        assert!(s.reason.contains("unlikely"));
+        // No throughput estimate: outbid most running jobs
+        let s = suggest(&market(0, 0.0), &request(1));
+        assert_eq!((s.bid, s.wait_secs), (325.0, None));
+        // A floor beyond the bid range is capped at the highest bid
+        let mut expensive = market(0, 5.0);
+        expensive.min_running_prices = vec![2400.0, 2600.0];
+        assert_eq!(candidate_bids(&expensive), vec![MAX_BID]);
+        assert_eq!(suggest(&expensive, &request(1)).bid, MAX_BID);
+    }
+
+    #[test]
+    fn builds_market_from_queue_and_pool() {
+        let now = 1_754_700_000;
+        let jobs: Vec<JobAd> = serde_json::from_value(json!([
+            {"ClusterId": 1, "ProcId": 0, "JobPrio": -700, "JobStatus": 2, "RequestGPUs": 2,
+             "RemoteHost": "slot1_1@a100-1", "JobCurrentStartDate": now - 3600,
+             "MachineAttrMinRunningPrice0": 120},
+            {"ClusterId": 2, "ProcId": 0, "JobPrio": -900, "JobStatus": 2, "RequestGPUs": 1,
+             "RemoteHost": "slot1_2@v100-1", "JobCurrentStartDate": now - 600},
+            {"ClusterId": 3, "ProcId": 0, "JobPrio": -600, "JobStatus": 1, "RequestGPUs": 4,
+             "Requirements": "/Expr(TARGET.CUDAGlobalMemoryMb > 60000)/"},
+            {"ClusterId": 4, "ProcId": 0, "JobPrio": -500, "JobStatus": 1, "RequestGPUs": 1,
+             "Requirements": "/Expr(TARGET.CUDAGlobalMemoryMb > 90000)/"},
+            {"ClusterId": 5, "ProcId": 0, "JobPrio": 0, "JobStatus": 1, "RequestGPUs": 0}
+        ]))
+        .unwrap();
+        let slots: Vec<SlotAd> = serde_json::from_value(json!([
+            {"Machine": "a100-1", "State": "Unclaimed", "GPUs": 2, "Memory": 200000,
+             "TotalGPUs": 8, "TotalMemory": 1000000, "CUDAGlobalMemoryMb": 81920},
+            {"Machine": "a100-1", "State": "Claimed", "GPUs": 2, "Memory": 100000,
+             "TotalGPUs": 8, "TotalMemory": 1000000, "CUDAGlobalMemoryMb": 81920},
+            {"Machine": "v100-1", "State": "Unclaimed", "GPUs": 4, "Memory": 100000,
+             "TotalGPUs": 4, "TotalMemory": 500000, "CUDAGlobalMemoryMb": 32768}
+        ]))
+        .unwrap();
+        let m = build_market(&jobs, &slots, &request(2), now);
+        assert_eq!(
+            (m.matching_machines, m.matching_gpus, m.free_gpus),
+            (1, 8, 2)
+        );
+        // The 90 GB job cannot run there and the CPU job does not compete
+        assert_eq!(m.idle, vec![(400.0, 4)]);
+        assert_eq!(m.running, vec![300.0]);
+        assert_eq!(m.floor(), Some(120.0));
+        assert_eq!(m.start_rate, 2.0 / 24.0);
+    }
+
+    #[test]
+    fn fails_without_matching_machines() {
+        let exec = FixtureExecutor::new("barmstrong")
+            .with_stdout(
+                "condor_q -json -attributes ClusterId,ProcId,JobPrio,JobStatus,RequestGPUs,Requirements,RemoteHost,JobCurrentStartDate,MachineAttrMinRunningPrice0",
+                include_str!("../../example.json"),
+            )
+            .with_stdout(&format!("condor_status -json -attributes {}", SLOT_ATTRS), "[]");
+        let err = handle_bid_suggest(&exec, &request(2), OutputFormat::Table).unwrap_err();
+        assert!(err.to_string().contains("2 GPUs, >= 58.6G per GPU"));
+    }
+}
@@ -0,0 +1,387 @@
+use crate::cmd::condor::{JobAd, JobAttr, JobStatus};
+use crate::utils::time::human_duration;
+use clap::ValueEnum;
+use comfy_table::{Attribute, Cell, CellAlignment, Color};
+use serde_json::{json, Value};
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+/// Columns shown by `mct ls` when neither `--columns` nor the profile's `ls_columns` is set.
+pub const DEFAULT_COLUMNS: &[Column] = &[
+    Column::Id,
+    Column::Status,
+    Column::Cmd,
+    Column::Args,
+    Column::Runtime,
+    Column::Remaining,
+    Column::Gpus,
+    Column::Bid,
+];
+
+/// Named columns available to `mct ls`.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    /// ClusterId.ProcId
+    Id,
+    /// Job status
+    Status,
+    /// Executable
+    Cmd,
+    /// Arguments
+    Args,
+    /// Time since the job first started
+    Runtime,
+    /// Time until removal for exceeding the runtime limit
+    Remaining,
+    /// Requested GPUs
+    Gpus,
+    /// Bid derived from JobPrio
+    Bid,
+    /// Requested / provisioned CPUs
+    Cpus,
+    /// Requested / provisioned memory
+    Memory,
+    /// Execute slot the job runs on
+    Host,
+    /// GPU devices assigned by the execute node
+    AssignedGpus,
+    /// Submission time
+    Queued,
+    /// Time spent waiting in the queue before starting (or so far)
+    Wait,
+    /// HoldReasonCode and HoldReason of held jobs
+    HoldReason,
+}
+
+/// Per-invocation values shared by all cells of a table.
+pub struct RowContext {
+    pub now: i64,
+    pub warn_within: u64,
+}
+
+impl Column {
+    /// Parse a column name as accepted by `--columns`, for values read from the config file.
+    pub fn parse(name: &str) -> Result<Column, String> {
+        Column::from_str(name.trim(), true).map_err(|_| {
+            let names: Vec<String> = Column::value_variants()
+                .iter()
+                .filter_map(|c| c.to_possible_value().map(|v| v.get_name().to_string()))
+                .collect();
+            format!(
+                "unknown column '{}' (expected one of: {})",
+                name,
+                names.join(", ")
+            )
+        })
+    }
+
+    pub fn header(&self) -> &'static str {
+        match self {
+            Column::Id => "JobID",
+            Column::Status => "Status",
+            Column::Cmd => "Cmd",
+            Column::Args => "Args",
+            Column::Runtime => "Runtime",
+            Column::Remaining => "Remaining",
+            Column::Gpus => "GPUs",
+            Column::Bid => "Bid",
+            Column::Cpus => "CPUs",
+            Column::Memory => "Memory",
+            Column::Host => "Host",
+            Column::AssignedGpus => "AssignedGPUs",
+            Column::Queued => "Queued",
+            Column::Wait => "Wait",
+            Column::HoldReason => "Hold Reason",
+        }
+    }
+
+    fn right_aligned(&self) -> bool {
+        matches!(
+            self,
+            Column::Gpus | Column::Bid | Column::Cpus | Column::Memory
+        )
+    }
+
+    /// Attributes that must be projected to fill this column.
+    pub fn attrs(&self) -> &'static [JobAttr] {
+        match self {
+            Column::Id => &[],
+            Column::Status => &[
+                JobAttr::JobStatus,
+                JobAttr::HoldReason,
+                JobAttr::HoldReasonCode,
+            ],
+            Column::Cmd => &[JobAttr::Cmd],
+            Column::Args => &[JobAttr::Args],
+            Column::Runtime => &[JobAttr::JobStartDate],
+            Column::Remaining => JobAd::removal_attrs(),
+            Column::Gpus => &[JobAttr::RequestGpus],
+            Column::Bid => &[JobAttr::JobPrio],
+            Column::Cpus => &[JobAttr::RequestCpus, JobAttr::CpusProvisioned],
+            Column::Memory => &[JobAttr::RequestMemory, JobAttr::MemoryProvisioned],
+            Column::Host => &[JobAttr::RemoteHost],
+            Column::AssignedGpus => &[JobAttr::AssignedGpus],
+            Column::Queued => &[JobAttr::QDate],
+            Column::Wait => &[JobAttr::QDate, JobAttr::JobStartDate],
+            Column::HoldReason => &[
+                JobAttr::JobStatus,
+                JobAttr::HoldReason,
+                JobAttr::HoldReasonCode,
+                JobAttr::HoldReasonSubCode,
+            ],
+        }
+    }
+
+    /// Field names this column contributes to machine-readable output.
+    pub fn fields(&self) -> &'static [&'static str] {
+        match self {
+            Column::Id => &["job_id", "cluster_id", "proc_id"],
+            Column::Status => &["status"],
+            Column::Cmd => &["cmd"],
+            Column::Args => &["args"],
+            Column::Runtime => &["job_start_date", "runtime_secs"],
+            Column::Remaining => &["remaining_secs"],
+            Column::Gpus => &["gpus"],
+            Column::Bid => &["bid"],
+            Column::Cpus => &["request_cpus", "cpus_provisioned"],
+            Column::Memory => &["request_memory_mb", "memory_provisioned_mb"],
+            Column::Host => &["remote_host"],
+            Column::AssignedGpus => &["assigned_gpus"],
+            Column::Queued => &["q_date"],
+            Column::Wait => &["wait_secs"],
+            Column::HoldReason => &["hold_reason_code", "hold_reason_sub_code", "hold_reason"],
+        }
+    }
+
+    /// Raw values for [`Column::fields`], in the same order.
+    pub fn values(&self, job: &JobAd, ctx: &RowContext) -> Vec<Value> {
+        match self {
+            Column::Id => vec![json!(job.id()), json!(job.cluster_id), json!(job.proc_id)],
+            Column::Status => vec![json!(job.job_status.map(|s| s.label()))],
+            Column::Cmd => vec![json!(job.cmd)],
+            Column::Args => vec![json!(job.args)],
+            Column::Runtime => vec![json!(job.job_start_date), json!(runtime_secs(job, ctx.now))],
+            Column::Remaining => vec![json!(remaining_secs(job, ctx.now))],
+            Column::Gpus => vec![json!(job.gpus())],
+            Column::Bid => vec![json!(job.price())],
+            Column::Cpus => vec![json!(job.request_cpus), json!(job.cpus_provisioned)],
+            Column::Memory => vec![json!(job.request_memory), json!(job.memory_provisioned)],
+            Column::Host => vec![json!(job.remote_host)],
+            Column::AssignedGpus => vec![json!(job.assigned_gpus)],
+            Column::Queued => vec![json!(job.q_date)],
+            Column::Wait => vec![json!(wait_secs(job, ctx.now))],
+            Column::HoldReason => vec![
+                json!(job.hold_reason_code),
+                json!(job.hold_reason_sub_code),
+                json!(job.hold_reason),
+            ],
+        }
+    }
+
+    pub fn header_cell(&self) -> Cell {
+        let cell = Cell::new(self.header()).add_attribute(Attribute::Bold);
+        if self.right_aligned() {
+            cell.set_alignment(CellAlignment::Right)
+        } else {
+            cell
+        }
+    }
+
+    pub fn cell(&self, job: &JobAd, ctx: &RowContext) -> Cell {
+        let cell = match self {
+            Column::Id => Cell::new(job.id()).fg(Color::Green),
+            Column::Status => {
+                let status = job.status();
+                Cell::new(status.label()).fg(table_color(status.color()))
+            }
+            Column::Cmd => Cell::new(job.cmd.as_deref().unwrap_or("")),
+            Column::Args => Cell::new(job.args.as_deref().unwrap_or("")),
+            Column::Runtime => Cell::new(
+                runtime_secs(job, ctx.now)
+                    .map(|s| human_duration(s as u64))
+                    .unwrap_or_else(|| "-".to_string()),
+            ),
+            Column::Remaining => match remaining_secs(job, ctx.now) {
+                Some(secs) => {
+                    let cell = Cell::new(human_duration(secs as u64));
+                    if secs as u64 <= ctx.warn_within {
+                        cell.fg(Color::Red)
+                    } else {
+                        cell
+                    }
+                }
+                None => Cell::new("-").fg(Color::DarkGrey),
+            },
+            Column::Gpus => Cell::new(job.gpus().to_string()),
+            Column::Bid => Cell::new(format!("{:.0}", job.price())),
+            Column::Cpus => Cell::new(requested_vs_provisioned(
+                job.request_cpus,
+                job.cpus_provisioned,
+                |v| v.to_string(),
+            )),
+            Column::Memory => Cell::new(requested_vs_provisioned(
+                job.request_memory,
+                job.memory_provisioned,
+                format_mb,
+            )),
+            Column::Host => Cell::new(job.remote_host.as_deref().unwrap_or("-")),
+            Column::AssignedGpus => Cell::new(job.assigned_gpus.as_deref().unwrap_or("-")),
+            Column::Queued => Cell::new(format_unix(job.q_date)),
+            Column::Wait => Cell::new(
+                wait_secs(job, ctx.now)
+                    .map(|s| human_duration(s as u64))
+                    .unwrap_or_else(|| "-".to_string()),
+            ),
+            Column::HoldReason => match hold_summary(job) {
+                Some(reason) => Cell::new(reason).fg(Color::Yellow),
+                None => Cell::new("-").fg(Color::DarkGrey),
+            },
+        };
+        if self.right_aligned() {
+            cell.set_alignment(CellAlignment::Right)
+        } else {
+            cell
+        }
+    }
+}
+
+/// `[code] reason` for held jobs.
+pub fn hold_summary(job: &JobAd) -> Option<String> {
+    if job.status() != JobStatus::Held {
+        return None;
+    }
+    let reason = job.hold_reason.as_deref().unwrap_or("no reason given");
+    Some(match job.hold_reason_code {
+        Some(code) => format!("[{}] {}", code, reason),
+        None => reason.to_string(),
+    })
+}
+
+// Status colors are crossterm colors; comfy-table mirrors them in its own enum
+fn table_color(color: crossterm::style::Color) -> Color {
+    use crossterm::style::Color as C;
+    match color {
+        C::Blue => Color::Blue,
+        C::Green => Color::Green,
+        C::DarkRed => Color::DarkRed,
+        C::DarkGreen => Color::DarkGreen,
+        C::Yellow => Color::Yellow,
+        C::Cyan => Color::Cyan,
+        C::Magenta => Color::Magenta,
+        C::White => Color::White,
+        _ => Color::Reset,
+    }
+}
+
+/// Attributes needed for all of `columns`.
+pub fn column_attrs(columns: &[Column]) -> Vec<JobAttr> {
+    columns
+        .iter()
+        .flat_map(|c| c.attrs().iter().copied())
+        .collect()
+}
+
+pub fn runtime_secs(job: &JobAd, now: i64) -> Option<i64> {
+    job.job_start_date
+        .filter(|s| *s > 0)
+        .map(|s| (now - s).max(0))
+}
+
+/// Seconds until a job is removed for hitting its wall-clock limit.
+pub fn remaining_secs(job: &JobAd, now: i64) -> Option<i64> {
+    job.removal_time(now).map(|t| (t - now).max(0))
+}
+
+/// Time between submission and the first start, or until now if not started yet.
+fn wait_secs(job: &JobAd, now: i64) -> Option<i64> {
+    let queued = job.q_date.filter(|q| *q > 0)?;
+    let until = job.job_start_date.filter(|s| *s > 0).unwrap_or(now);
+    Some((until - queued).max(0))
+}
+
+fn requested_vs_provisioned(
+    requested: Option<i64>,
+    provisioned: Option<i64>,
+    fmt: impl Fn(i64) -> String,
+) -> String {
+    match (requested, provisioned) {
+        (None, None) => "-".to_string(),
+        (r, p) => format!(
+            "{} / {}",
+            r.map(&fmt).unwrap_or_else(|| "-".to_string()),
+            p.map(&fmt).unwrap_or_else(|| "-".to_string())
+        ),
+    }
+}
+
+/// Format a size in MB (as used by `RequestMemory`) with a G suffix above 1 GB.
+pub fn format_mb(mb: i64) -> String {
+    if mb >= 1024 {
+        format!("{:.1}G", mb as f64 / 1024.0)
+    } else {
+        format!("{}M", mb)
+    }
+}
+
+pub fn format_unix(ts: Option<i64>) -> String {
+    ts.filter(|t| *t > 0)
+        .and_then(|t| OffsetDateTime::from_unix_timestamp(t).ok())
+        .and_then(|d| d.format(&Rfc3339).ok())
+        .unwrap_or_else(|| "-".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::condor::projection;
+
+    fn example_job() -> JobAd {
+        let jobs: Vec<JobAd> = serde_json::from_str(include_str!("../../example.json")).unwrap();
+        jobs.into_iter().next().unwrap()
+    }
+
+    #[test]
+    fn parses_column_names_case_insensitively() {
+        assert_eq!(Column::parse("Memory"), Ok(Column::Memory));
+        assert_eq!(Column::parse("assigned-gpus"), Ok(Column::AssignedGpus));
+        assert!(Column::parse("nope")
+            .unwrap_err()
+            .contains("expected one of"));
+    }
+
+    #[test]
+    fn attributes_follow_selected_columns() {
+        let attrs = column_attrs(&[Column::Id, Column::Memory, Column::Host]);
+        assert_eq!(
+            projection(&attrs),
+            "ClusterId,ProcId,RequestMemory,MemoryProvisioned,RemoteHost"
+        );
+    }
+
+    #[test]
+    fn resource_cells_show_requested_and_provisioned() {
+        let job = example_job();
+        let ctx = RowContext {
+            now: job.job_start_date.unwrap() + 60,
+            warn_within: 0,
+        };
+        assert_eq!(Column::Memory.cell(&job, &ctx).content(), "62.5G / 62.5G");
+        assert_eq!(
+            Column::Wait.values(&job, &ctx).len(),
+            Column::Wait.fields().len()
+        );
+        assert_eq!(format_mb(512), "512M");
+    }
+
+    #[test]
+    fn hold_reason_only_for_held_jobs() {
+        let mut job = example_job();
+        job.hold_reason = Some("Job has gone over cgroup memory limit".into());
+        job.hold_reason_code = Some(34);
+        assert_eq!(hold_summary(&job), None);
+        job.job_status = Some(JobStatus::Held);
+        assert_eq!(
+            hold_summary(&job).as_deref(),
+            Some("[34] Job has gone over cgroup memory limit")
+        );
+    }
+}
@@ -1,15 +1,594 @@
-use crate::config::LoginConfig;
-use crate::utils::ssh::{parse_json_relaxed, run_remote};
+use crate::utils::classad::{first_true_time, lower_bound, parse, ClassAdEnv};
+use crate::utils::remote::RemoteExecutor;
+use crate::utils::serde::{deserialize_opt_f64_lenient, deserialize_opt_i64_lenient, ClassAdValue};
+use crate::utils::ssh::{parse_json_relaxed, shell_escape_single_quotes};
+use crossterm::style::Color;
 use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+/// HTCondor `JobStatus` codes.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(from = "i64")]
+pub enum JobStatus {
+    Idle,
+    Running,
+    Removed,
+    Completed,
+    Held,
+    TransferringOutput,
+    Suspended,
+    Unknown(i64),
+}
+
+impl From<i64> for JobStatus {
+    fn from(code: i64) -> Self {
+        match code {
+            1 => JobStatus::Idle,
+            2 => JobStatus::Running,
+            3 => JobStatus::Removed,
+            4 => JobStatus::Completed,
+            5 => JobStatus::Held,
+            6 => JobStatus::TransferringOutput,
+            7 => JobStatus::Suspended,
+            other => JobStatus::Unknown(other),
+        }
+    }
+}
+
+impl JobStatus {
+    pub fn code(&self) -> i64 {
+        match self {
+            JobStatus::Idle => 1,
+            JobStatus::Running => 2,
+            JobStatus::Removed => 3,
+            JobStatus::Completed => 4,
+            JobStatus::Held => 5,
+            JobStatus::TransferringOutput => 6,
+            JobStatus::Suspended => 7,
+            JobStatus::Unknown(code) => *code,
+        }
+    }
+
+    /// Single-letter abbreviation as used by the jobs TUI.
+    pub fn letter(&self) -> char {
+        match self {
+            JobStatus::Idle => 'I',
+            JobStatus::Running => 'R',
+            JobStatus::Removed => 'X',
+            JobStatus::Completed => 'C',
+            JobStatus::Held => 'H',
+            JobStatus::TransferringOutput => 'O',
+            JobStatus::Suspended => 'S',
+            JobStatus::Unknown(_) => '?',
+        }
+    }
+
+    /// Color of the status in the jobs TUI and the `mct ls` status column.
+    pub fn color(&self) -> Color {
+        match self {
+            JobStatus::Idle => Color::Blue,
+            JobStatus::Running => Color::Green,
+            JobStatus::Removed => Color::DarkRed,
+            JobStatus::Completed => Color::DarkGreen,
+            JobStatus::Held => Color::Yellow,
+            JobStatus::TransferringOutput => Color::Cyan,
+            JobStatus::Suspended => Color::Magenta,
+            JobStatus::Unknown(_) => Color::White,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            JobStatus::Idle => "Idle",
+            JobStatus::Running => "Running",
+            JobStatus::Removed => "Removed",
+            JobStatus::Completed => "Completed",
+            JobStatus::Held => "Held",
+            JobStatus::TransferringOutput => "Transferring Output",
+            JobStatus::Suspended => "Suspended",
+            JobStatus::Unknown(_) => "Unknown",
+        }
+    }
+}
+
+impl std::str::FromStr for JobStatus {
+    type Err = String;
+
+    /// Accepts a status name (`idle`, `held`, `transferring-output`, ...), its
+    /// TUI letter or the numeric code.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let normalized = s.trim().to_ascii_lowercase().replace(['-', '_', ' '], "");
+        let status = match normalized.as_str() {
+            "idle" | "i" => JobStatus::Idle,
+            "running" | "r" => JobStatus::Running,
+            "removed" | "x" => JobStatus::Removed,
+            "completed" | "c" => JobStatus::Completed,
+            "held" | "h" => JobStatus::Held,
+            "transferringoutput" | "transferring" | "o" => JobStatus::TransferringOutput,
+            "suspended" | "s" => JobStatus::Suspended,
+            other => match other.parse::<i64>() {
+                Ok(code) => JobStatus::from(code),
+                Err(_) => return Err(format!("unknown job status '{}'", s)),
+            },
+        };
+        Ok(status)
+    }
+}
+
+/// HTCondor `JobUniverse` codes.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(from = "i64")]
+pub enum Universe {
+    Standard,
+    Vanilla,
+    Scheduler,
+    Grid,
+    Java,
+    Parallel,
+    Local,
+    Vm,
+    Other(i64),
+}
+
+impl From<i64> for Universe {
+    fn from(code: i64) -> Self {
+        match code {
+            1 => Universe::Standard,
+            5 => Universe::Vanilla,
+            7 => Universe::Scheduler,
+            9 => Universe::Grid,
+            10 => Universe::Java,
+            11 => Universe::Parallel,
+            12 => Universe::Local,
+            13 => Universe::Vm,
+            other => Universe::Other(other),
+        }
+    }
+}
+
+impl Universe {
+    pub fn code(&self) -> i64 {
+        match self {
+            Universe::Standard => 1,
+            Universe::Vanilla => 5,
+            Universe::Scheduler => 7,
+            Universe::Grid => 9,
+            Universe::Java => 10,
+            Universe::Parallel => 11,
+            Universe::Local => 12,
+            Universe::Vm => 13,
+            Universe::Other(code) => *code,
+        }
+    }
+}
+
+/// Job ClassAd attributes understood by [`JobAd`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobAttr {
+    ClusterId,
+    ProcId,
+    Cmd,
+    Args,
+    Iwd,
+    Out,
+    Err,
+    UserLog,
+    JobStatus,
+    JobPrio,
+    RequestCpus,
+    RequestGpus,
+    RequestMemory,
+    CpusProvisioned,
+    GpusProvisioned,
+    MemoryProvisioned,
+    QDate,
+    JobStartDate,
+    JobCurrentStartDate,
+    EnteredCurrentStatus,
+    CompletionDate,
+    RemoteWallClockTime,
+    RemoteHost,
+    AssignedGpus,
+    ExitCode,
+    ExitBySignal,
+    ExitSignal,
+    HoldReason,
+    HoldReasonCode,
+    HoldReasonSubCode,
+    MachineAttrMinRunningPrice0,
+    Requirements,
+    PeriodicRemove,
+    MaxJobRetirementTime,
+}
+
+impl JobAttr {
+    pub fn name(&self) -> &'static str {
+        match self {
+            JobAttr::ClusterId => "ClusterId",
+            JobAttr::ProcId => "ProcId",
+            JobAttr::Cmd => "Cmd",
+            JobAttr::Args => "Args",
+            JobAttr::Iwd => "Iwd",
+            JobAttr::Out => "Out",
+            JobAttr::Err => "Err",
+            JobAttr::UserLog => "UserLog",
+            JobAttr::JobStatus => "JobStatus",
+            JobAttr::JobPrio => "JobPrio",
+            JobAttr::RequestCpus => "RequestCpus",
+            JobAttr::RequestGpus => "RequestGPUs",
+            JobAttr::RequestMemory => "RequestMemory",
+            JobAttr::CpusProvisioned => "CpusProvisioned",
+            JobAttr::GpusProvisioned => "GPUsProvisioned",
+            JobAttr::MemoryProvisioned => "MemoryProvisioned",
+            JobAttr::QDate => "QDate",
+            JobAttr::JobStartDate => "JobStartDate",
+            JobAttr::JobCurrentStartDate => "JobCurrentStartDate",
+            JobAttr::EnteredCurrentStatus => "EnteredCurrentStatus",
+            JobAttr::CompletionDate => "CompletionDate",
+            JobAttr::RemoteWallClockTime => "RemoteWallClockTime",
+            JobAttr::RemoteHost => "RemoteHost",
+            JobAttr::AssignedGpus => "AssignedGPUs",
+            JobAttr::ExitCode => "ExitCode",
+            JobAttr::ExitBySignal => "ExitBySignal",
+            JobAttr::ExitSignal => "ExitSignal",
+            JobAttr::HoldReason => "HoldReason",
+            JobAttr::HoldReasonCode => "HoldReasonCode",
+            JobAttr::HoldReasonSubCode => "HoldReasonSubCode",
+            JobAttr::MachineAttrMinRunningPrice0 => "MachineAttrMinRunningPrice0",
+            JobAttr::Requirements => "Requirements",
+            JobAttr::PeriodicRemove => "PeriodicRemove",
+            JobAttr::MaxJobRetirementTime => "MaxJobRetirementTime",
+        }
+    }
+}
+
+/// Build the comma-separated `-attributes` list for the given attributes.
+/// `ClusterId` and `ProcId` are always included since every [`JobAd`] needs them.
+pub fn projection(attrs: &[JobAttr]) -> String {
+    let mut names: Vec<&str> = vec![JobAttr::ClusterId.name(), JobAttr::ProcId.name()];
+    for attr in attrs {
+        if !names.contains(&attr.name()) {
+            names.push(attr.name());
+        }
+    }
+    names.join(",")
+}
+
+/// A job ClassAd as returned by `condor_q -json` / `condor_history -json`.
+/// Attributes that were not projected (or not set) are `None`.
+#[derive(Deserialize, Debug, Clone)]
+pub struct JobAd {
+    #[serde(rename = "ClusterId")]
+    pub cluster_id: i64,
+    #[serde(rename = "ProcId")]
+    pub proc_id: i64,
+    #[serde(rename = "Owner")]
+    pub owner: Option<String>,
+    #[serde(rename = "Cmd")]
+    pub cmd: Option<String>,
+    #[serde(rename = "Args")]
+    pub args: Option<String>,
+    #[serde(rename = "Iwd")]
+    pub iwd: Option<String>,
+    #[serde(rename = "Out")]
+    pub out: Option<String>,
+    #[serde(rename = "Err")]
+    pub err: Option<String>,
+    #[serde(rename = "UserLog")]
+    pub user_log: Option<String>,
+
+    #[serde(rename = "JobStatus")]
+    pub job_status: Option<JobStatus>,
+    #[serde(rename = "JobUniverse")]
+    pub job_universe: Option<Universe>,
+    #[serde(
+        rename = "JobPrio",
+        default,
+        deserialize_with = "deserialize_opt_i64_lenient"
+    )]
+    pub job_prio: Option<i64>,
+
+    // Resources requested
+    #[serde(
+        rename = "RequestCpus",
+        default,
+        deserialize_with = "deserialize_opt_i64_lenient"
+    )]
+    pub request_cpus: Option<i64>,
+    #[serde(
+        rename = "RequestGPUs",
+        default,
+        deserialize_with = "deserialize_opt_i64_lenient"
+    )]
+    pub request_gpus: Option<i64>,
+    #[serde(
+        rename = "RequestMemory",
+        default,
+        deserialize_with = "deserialize_opt_i64_lenient"
+    )]
+    pub request_memory: Option<i64>,
+    /// Usually the expression `DiskUsage`
+    #[serde(rename = "RequestDisk")]
+    pub request_disk: Option<ClassAdValue>,
+
+    // Resources provisioned by the matched slot
+    #[serde(
+        rename = "CpusProvisioned",
+        default,
+        deserialize_with = "deserialize_opt_i64_lenient"
+    )]
+    pub cpus_provisioned: Option<i64>,
+    #[serde(
+        rename = "GPUsProvisioned",
+        default,
+        deserialize_with = "deserialize_opt_i64_lenient"
+    )]
+    pub gpus_provisioned: Option<i64>,
+    #[serde(
+        rename = "MemoryProvisioned",
+        default,
+        deserialize_with = "deserialize_opt_i64_lenient"
+    )]
+    pub memory_provisioned: Option<i64>,
+    #[serde(
+        rename = "DiskProvisioned",
+        default,
+        deserialize_with = "deserialize_opt_i64_lenient"
+    )]
+    pub disk_provisioned: Option<i64>,
+    #[serde(
+        rename = "DiskUsage",
+        default,
+        deserialize_with = "deserialize_opt_i64_lenient"
+    )]
+    pub disk_usage: Option<i64>,
+
+    // Timestamps (seconds since epoch)
+    #[serde(
+        rename = "QDate",
+        default,
+        deserialize_with = "deserialize_opt_i64_lenient"
+    )]
+    pub q_date: Option<i64>,
+    #[serde(
+        rename = "JobStartDate",
+        default,
+        deserialize_with = "deserialize_opt_i64_lenient"
+    )]
+    pub job_start_date: Option<i64>,
+    #[serde(
+        rename = "JobCurrentStartDate",
+        default,
+        deserialize_with = "deserialize_opt_i64_lenient"
+    )]
+    pub job_current_start_date: Option<i64>,
+    #[serde(
+        rename = "EnteredCurrentStatus",
+        default,
+        deserialize_with = "deserialize_opt_i64_lenient"
+    )]
+    pub entered_current_status: Option<i64>,
+    #[serde(
+        rename = "CompletionDate",
+        default,
+        deserialize_with = "deserialize_opt_i64_lenient"
+    )]
+    pub completion_date: Option<i64>,
+    #[serde(
+        rename = "RemoteWallClockTime",
+        default,
+        deserialize_with = "deserialize_opt_f64_lenient"
+    )]
+    pub remote_wall_clock_time: Option<f64>,
+
+    // Execution placement
+    #[serde(rename = "RemoteHost")]
+    pub remote_host: Option<String>,
+    #[serde(rename = "AssignedGPUs")]
+    pub assigned_gpus: Option<String>,
+
+    // Exit and hold information
+    #[serde(
+        rename = "ExitCode",
+        default,
+        deserialize_with = "deserialize_opt_i64_lenient"
+    )]
+    pub exit_code: Option<i64>,
+    #[serde(rename = "ExitBySignal")]
+    pub exit_by_signal: Option<bool>,
+    #[serde(
+        rename = "ExitSignal",
+        default,
+        deserialize_with = "deserialize_opt_i64_lenient"
+    )]
+    pub exit_signal: Option<i64>,
+    #[serde(rename = "HoldReason")]
+    pub hold_reason: Option<String>,
+    #[serde(
+        rename = "HoldReasonCode",
+        default,
+        deserialize_with = "deserialize_opt_i64_lenient"
+    )]
+    pub hold_reason_code: Option<i64>,
+    #[serde(
+        rename = "HoldReasonSubCode",
+        default,
+        deserialize_with = "deserialize_opt_i64_lenient"
+    )]
+    pub hold_reason_sub_code: Option<i64>,
+
+    #[serde(
+        rename = "MachineAttrMinRunningPrice0",
+        default,
+        deserialize_with = "deserialize_opt_f64_lenient"
+    )]
+    pub machine_attr_min_running_price0: Option<f64>,
+
+    // Policy expressions
+    #[serde(rename = "Requirements")]
+    pub requirements: Option<ClassAdValue>,
+    #[serde(rename = "PeriodicRemove")]
+    pub periodic_remove: Option<ClassAdValue>,
+    /// Seconds after the current start the job may keep running; may be an expression
+    #[serde(rename = "MaxJobRetirementTime")]
+    pub max_job_retirement_time: Option<ClassAdValue>,
+}
+
+// Machine attributes holding per-GPU memory in MB, depending on the HTCondor version
+const GPU_MEMORY_ATTRS: &[&str] = &[
+    "CUDAGlobalMemoryMb",
+    "GPUs_GlobalMemoryMb",
+    "GlobalMemoryMb",
+];
+
+// How far ahead PeriodicRemove is searched for a wall-clock limit
+const REMOVAL_HORIZON_SECS: i64 = 365 * 86400;
+
+impl JobAd {
+    pub fn id(&self) -> String {
+        format!("{}.{}", self.cluster_id, self.proc_id)
+    }
+
+    /// Requested GPU count, treating a missing or unevaluated request as 0.
+    pub fn gpus(&self) -> i64 {
+        self.request_gpus.unwrap_or(0)
+    }
+
+    pub fn status(&self) -> JobStatus {
+        self.job_status.unwrap_or(JobStatus::Unknown(0))
+    }
+
+    /// Bid derived from `JobPrio`.
+    pub fn price(&self) -> f64 {
+        job_prio_to_price(self.job_prio.unwrap_or(0))
+    }
+
+    /// Unix time at which a running job hits its wall-clock limit, derived from
+    /// `PeriodicRemove` or `MaxJobRetirementTime`, whichever comes first.
+    pub fn removal_time(&self, now: i64) -> Option<i64> {
+        if self.status() != JobStatus::Running {
+            return None;
+        }
+        let from_remove = match &self.periodic_remove {
+            Some(ClassAdValue::Expr(src)) => parse(src)
+                .ok()
+                .and_then(|expr| first_true_time(&expr, self, now, REMOVAL_HORIZON_SECS)),
+            _ => None,
+        };
+        let from_retirement = self
+            .max_job_retirement_time
+            .as_ref()
+            .and_then(|v| v.eval(self).as_i64())
+            .filter(|limit| *limit > 0)
+            .zip(self.job_current_start_date)
+            .map(|(limit, start)| start + limit);
+        from_remove.into_iter().chain(from_retirement).min()
+    }
+
+    /// Minimum GPU memory in MB demanded by `Requirements`, e.g. 60000 for
+    /// `TARGET.CUDAGlobalMemoryMb > 60000`.
+    pub fn gpu_memory_requirement(&self) -> Option<i64> {
+        let Some(ClassAdValue::Expr(src)) = &self.requirements else {
+            return None;
+        };
+        let expr = parse(src).ok()?;
+        GPU_MEMORY_ATTRS
+            .iter()
+            .filter_map(|attr| lower_bound(&expr, attr))
+            .reduce(f64::max)
+            .map(|mb| mb as i64)
+    }
+
+    /// Attributes [`JobAd::removal_time`] depends on.
+    pub fn removal_attrs() -> &'static [JobAttr] {
+        &[
+            JobAttr::JobStatus,
+            JobAttr::JobStartDate,
+            JobAttr::JobCurrentStartDate,
+            JobAttr::EnteredCurrentStatus,
+            JobAttr::RemoteWallClockTime,
+            JobAttr::PeriodicRemove,
+            JobAttr::MaxJobRetirementTime,
+        ]
+    }
+}
+
+fn int_attr(v: Option<i64>) -> Option<ClassAdValue> {
+    v.map(ClassAdValue::Int)
+}
+
+fn str_attr(v: &Option<String>) -> Option<ClassAdValue> {
+    v.as_ref().map(|s| ClassAdValue::Str(s.clone()))
+}
+
+impl ClassAdEnv for JobAd {
+    fn lookup(&self, name: &str) -> Option<ClassAdValue> {
+        match name.to_ascii_lowercase().as_str() {
+            "clusterid" => int_attr(Some(self.cluster_id)),
+            "procid" => int_attr(Some(self.proc_id)),
+            "owner" => str_attr(&self.owner),
+            "cmd" => str_attr(&self.cmd),
+            "args" => str_attr(&self.args),
+            "iwd" => str_attr(&self.iwd),
+            "jobstatus" => int_attr(self.job_status.map(|s| s.code())),
+            "jobuniverse" => int_attr(self.job_universe.map(|u| u.code())),
+            "jobprio" => int_attr(self.job_prio),
+            "requestcpus" => int_attr(self.request_cpus),
+            "requestgpus" => int_attr(self.request_gpus),
+            "requestmemory" => int_attr(self.request_memory),
+            "requestdisk" => self.request_disk.clone(),
+            "cpusprovisioned" => int_attr(self.cpus_provisioned),
+            "gpusprovisioned" => int_attr(self.gpus_provisioned),
+            "memoryprovisioned" => int_attr(self.memory_provisioned),
+            "diskprovisioned" => int_attr(self.disk_provisioned),
+            "diskusage" => int_attr(self.disk_usage),
+            "qdate" => int_attr(self.q_date),
+            "jobstartdate" => int_attr(self.job_start_date),
+            "jobcurrentstartdate" => int_attr(self.job_current_start_date),
+            "enteredcurrentstatus" => int_attr(self.entered_current_status),
+            "completiondate" => int_attr(self.completion_date),
+            "remotewallclocktime" => self.remote_wall_clock_time.map(ClassAdValue::Real),
+            "remotehost" => str_attr(&self.remote_host),
+            "assignedgpus" => str_attr(&self.assigned_gpus),
+            "exitcode" => int_attr(self.exit_code),
+            "exitbysignal" => self.exit_by_signal.map(ClassAdValue::Bool),
+            "exitsignal" => int_attr(self.exit_signal),
+            "holdreason" => str_attr(&self.hold_reason),
+            "holdreasoncode" => int_attr(self.hold_reason_code),
+            "holdreasonsubcode" => int_attr(self.hold_reason_sub_code),
+            "requirements" => self.requirements.clone(),
+            "periodicremove" => self.periodic_remove.clone(),
+            "maxjobretirementtime" => self.max_job_retirement_time.clone(),
+            _ => None,
+        }
+    }
+}
+
+pub fn job_prio_to_price(job_prio: i64) -> f64 {
+    // Convert JobPrio range [-1000, 1000] to price range [0, 2000]
+    (job_prio + 1000) as f64
+}
+
+/// Inverse of [`job_prio_to_price`], for building `JobPrio` constraints.
+pub fn price_to_job_prio(price: f64) -> f64 {
+    price - 1000.0
+}
 
 pub fn condor_q_for_user<T: DeserializeOwned>(
-    login: &LoginConfig,
+    exec: &dyn RemoteExecutor,
     username: &str,
     attrs: &str,
+    constraint: Option<&str>,
 ) -> Result<Vec<T>, Box<dyn std::error::Error>> {
-    let cmd = format!("condor_q {} -json -attributes {}", username, attrs);
-    let out = run_remote(login, &cmd)?;
-    if !out.status.success() {
+    let mut cmd = format!("condor_q {} -json -attributes {}", username, attrs);
+    if let Some(constraint) = constraint {
+        cmd.push_str(&format!(
+            " -constraint '{}'",
+            shell_escape_single_quotes(constraint)
+        ));
+    }
+    let out = exec.run(&cmd)?;
+    if !out.success() {
         let stderr = String::from_utf8_lossy(&out.stderr);
         return Err(format!("SSH command failed: {}", stderr).into());
     }
@@ -18,20 +597,119 @@ pub fn condor_q_for_user<T: DeserializeOwned>(
 }
 
 pub fn condor_history_for_user<T: DeserializeOwned>(
-    login: &LoginConfig,
+    exec: &dyn RemoteExecutor,
     username: &str,
     attrs: &str,
     limit: usize,
+    constraint: Option<&str>,
 ) -> Result<Vec<T>, Box<dyn std::error::Error>> {
-    let cmd = format!(
+    let mut cmd = format!(
         "condor_history {} -json -attributes {} -limit {}",
         username, attrs, limit
     );
-    let out = run_remote(login, &cmd)?;
-    if !out.status.success() {
+    if let Some(constraint) = constraint {
+        cmd.push_str(&format!(
+            " -constraint '{}'",
+            shell_escape_single_quotes(constraint)
+        ));
+    }
+    let out = exec.run(&cmd)?;
+    if !out.success() {
         let stderr = String::from_utf8_lossy(&out.stderr);
         return Err(format!("SSH command failed: {}", stderr).into());
     }
     let v: Vec<T> = parse_json_relaxed(&out.stdout)?;
     Ok(v)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::remote::{CommandOutput, FixtureExecutor};
+
+    const EXAMPLE: &str = include_str!("../../example.json");
+
+    #[test]
+    fn condor_q_for_user_parses_captured_output() {
+        let exec = FixtureExecutor::new("barmstrong").with_stdout(
+            "condor_q barmstrong -json -attributes ClusterId,ProcId",
+            EXAMPLE,
+        );
+        let jobs: Vec<serde_json::Value> =
+            condor_q_for_user(&exec, "barmstrong", "ClusterId,ProcId", None).unwrap();
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0]["ClusterId"], 16337546);
+        assert_eq!(exec.calls().len(), 1);
+    }
+
+    #[test]
+    fn job_status_from_names_letters_and_codes() {
+        assert_eq!("held".parse::<JobStatus>(), Ok(JobStatus::Held));
+        assert_eq!("R".parse::<JobStatus>(), Ok(JobStatus::Running));
+        assert_eq!(
+            "transferring-output".parse::<JobStatus>(),
+            Ok(JobStatus::TransferringOutput)
+        );
+        assert_eq!("4".parse::<JobStatus>(), Ok(JobStatus::Completed));
+        assert!("stuck".parse::<JobStatus>().is_err());
+    }
+
+    #[test]
+    fn projection_always_includes_job_id() {
+        assert_eq!(
+            projection(&[JobAttr::Cmd, JobAttr::ProcId, JobAttr::Cmd]),
+            "ClusterId,ProcId,Cmd"
+        );
+    }
+
+    #[test]
+    fn job_ad_from_captured_output() {
+        let jobs: Vec<JobAd> = parse_json_relaxed(EXAMPLE.as_bytes()).unwrap();
+        let job = &jobs[0];
+        assert_eq!(job.id(), "16337546.0");
+        assert_eq!(job.status(), JobStatus::Running);
+        assert_eq!(job.job_universe, Some(Universe::Vanilla));
+        assert_eq!(job.gpus(), 1);
+        assert_eq!(job.memory_provisioned, Some(64000));
+        // RequestDisk is the expression `DiskUsage`
+        assert_eq!(
+            job.request_disk,
+            Some(ClassAdValue::Expr("DiskUsage".into()))
+        );
+        assert_eq!(
+            job.request_disk.as_ref().unwrap().eval(job),
+            ClassAdValue::Int(1)
+        );
+        assert_eq!(
+            job.lookup("JobUniverse"),
+            Some(ClassAdValue::Int(Universe::Vanilla.code()))
+        );
+        // Requirements only depend on the (absent) machine ad
+        let requirements = job.requirements.as_ref().unwrap();
+        assert_eq!(requirements.eval(job), ClassAdValue::Undefined);
+        // PeriodicRemove removes the job 16000s after JobCurrentStartDate
+        let start = job.job_current_start_date.unwrap();
+        assert_eq!(job.removal_time(start + 60), Some(start + 16000));
+        assert_eq!(
+            job.remote_host.as_deref(),
+            Some("slot1_5@g136.internal.cluster.is.localnet")
+        );
+        assert_eq!(job.price(), 250.0);
+    }
+
+    #[test]
+    fn condor_history_for_user_reports_remote_failure() {
+        let exec = FixtureExecutor::new("barmstrong").with_output(
+            "condor_history barmstrong -json -attributes ClusterId -limit 5",
+            CommandOutput {
+                code: Some(1),
+                stdout: Vec::new(),
+                stderr: b"condor_history: no such schedd".to_vec(),
+            },
+        );
+        let err =
+            condor_history_for_user::<serde_json::Value>(&exec, "barmstrong", "ClusterId", 5, None)
+                .unwrap_err();
+        assert!(err.to_string().contains("no such schedd"));
+    }
+}
@@ -0,0 +1,302 @@
+use crate::cmd::columns::format_mb;
+use crate::cmd::condor::{projection, JobAttr};
+use crate::cmd::logs::{find_job, parse_job_selector};
+use crate::utils::output::{OutputFormat, Records};
+use crate::utils::remote::{require_username, RemoteExecutor};
+use crate::utils::ssh::{build_path, shell_escape_single_quotes};
+use crate::utils::time::human_duration;
+use crate::utils::userlog::{parse_user_log, EventKind, Termination, UserLogEvent};
+use comfy_table::{presets::UTF8_FULL, Attribute, Cell, Color, ContentArrangement, Table};
+use serde_json::json;
+
+fn event_label(event: &UserLogEvent) -> String {
+    match &event.kind {
+        EventKind::Submit => "Submitted".into(),
+        EventKind::Execute { .. } => "Executing".into(),
+        EventKind::ExecutableError => "Executable error".into(),
+        EventKind::Evicted => "Evicted".into(),
+        EventKind::Terminated(_) => "Terminated".into(),
+        EventKind::ImageSize { .. } => "Memory peak".into(),
+        EventKind::Aborted { .. } => "Aborted".into(),
+        EventKind::Suspended => "Suspended".into(),
+        EventKind::Unsuspended => "Unsuspended".into(),
+        EventKind::Held { .. } => "Held".into(),
+        EventKind::Released { .. } => "Released".into(),
+        EventKind::FileTransfer { description } => description.clone(),
+        EventKind::Other(_) => event.title.trim_end_matches('.').to_string(),
+    }
+}
+
+fn termination_text(termination: &Option<Termination>) -> String {
+    match termination {
+        Some(Termination::Exit(code)) => format!("exited with code {}", code),
+        Some(Termination::Signal(sig)) => format!("killed by signal {}", sig),
+        None => "terminated".into(),
+    }
+}
+
+/// Memory used according to an image size update, in MB.
+fn memory_mb(kind: &EventKind) -> Option<i64> {
+    match kind {
+        EventKind::ImageSize {
+            memory_mb, rss_kb, ..
+        } => memory_mb.or(rss_kb.map(|kb| kb / 1024)),
+        _ => None,
+    }
+}
+
+fn event_details(event: &UserLogEvent) -> String {
+    match &event.kind {
+        EventKind::Execute { host } => host.clone().unwrap_or_default(),
+        EventKind::Terminated(t) => termination_text(t),
+        EventKind::ImageSize { .. } => memory_mb(&event.kind).map(format_mb).unwrap_or_default(),
+        EventKind::Held { reason, code, .. } => {
+            let reason = reason.as_deref().unwrap_or("");
+            match code {
+                Some(code) => format!("[{}] {}", code, reason),
+                None => reason.to_string(),
+            }
+        }
+        EventKind::Aborted { reason } | EventKind::Released { reason } => {
+            reason.clone().unwrap_or_default()
+        }
+        _ => event.details.first().cloned().unwrap_or_default(),
+    }
+}
+
+/// Events shown in the timeline: image size updates only when they set a new peak.
+fn timeline(events: &[UserLogEvent]) -> Vec<&UserLogEvent> {
+    let mut peak = 0;
+    events
+        .iter()
+        .filter(|e| match memory_mb(&e.kind) {
+            Some(mb) if mb > peak => {
+                peak = mb;
+                true
+            }
+            Some(_) => false,
+            None => !matches!(e.kind, EventKind::ImageSize { .. }),
+        })
+        .collect()
+}
+
+fn render_timeline(rows: &[&UserLogEvent]) -> Table {
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("Time").add_attribute(Attribute::Bold),
+            Cell::new("+Prev").add_attribute(Attribute::Bold),
+            Cell::new("Elapsed").add_attribute(Attribute::Bold),
+            Cell::new("Event").add_attribute(Attribute::Bold),
+            Cell::new("Details").add_attribute(Attribute::Bold),
+        ]);
+    let start = rows.first().map(|e| e.timestamp()).unwrap_or_default();
+    let mut prev = start;
+    for e in rows {
+        let ts = e.timestamp();
+        let color = match e.kind {
+            EventKind::Held { .. } | EventKind::Evicted | EventKind::Aborted { .. } => {
+                Color::Yellow
+            }
+            EventKind::Terminated(Some(Termination::Exit(0))) => Color::Green,
+            EventKind::Terminated(_) | EventKind::ExecutableError => Color::Red,
+            _ => Color::Reset,
+        };
+        table.add_row(vec![
+            Cell::new(e.display_time()),
+            Cell::new(human_duration((ts - prev).max(0) as u64)),
+            Cell::new(human_duration((ts - start).max(0) as u64)),
+            Cell::new(event_label(e)).fg(color),
+            Cell::new(event_details(e)),
+        ]);
+        prev = ts;
+    }
+    table
+}
+
+fn event_records(rows: &[&UserLogEvent]) -> Records {
+    let mut records = Records::new(vec![
+        "time",
+        "elapsed_secs",
+        "since_previous_secs",
+        "code",
+        "event",
+        "details",
+    ]);
+    let start = rows.first().map(|e| e.timestamp()).unwrap_or_default();
+    let mut prev = start;
+    for e in rows {
+        let ts = e.timestamp();
+        records.push(vec![
+            json!(e.display_time()),
+            json!(ts - start),
+            json!(ts - prev),
+            json!(e.code),
+            json!(event_label(e)),
+            json!(event_details(e)),
+        ]);
+        prev = ts;
+    }
+    records
+}
+
+/// Summary lines: queue wait, run time, peak memory and how the job ended.
+fn summarize(events: &[UserLogEvent]) -> Vec<String> {
+    let mut lines = Vec::new();
+    let submit = events.iter().find(|e| e.kind == EventKind::Submit);
+    let first_exec = events
+        .iter()
+        .find(|e| matches!(e.kind, EventKind::Execute { .. }));
+    if let (Some(s), Some(x)) = (submit, first_exec) {
+        lines.push(format!(
+            "Queued:      {}",
+            human_duration((x.timestamp() - s.timestamp()).max(0) as u64)
+        ));
+    }
+    if let (Some(x), Some(last)) = (first_exec, events.last()) {
+        lines.push(format!(
+            "Run time:    {} (first start to last event)",
+            human_duration((last.timestamp() - x.timestamp()).max(0) as u64)
+        ));
+    }
+    if let Some(peak) = events.iter().filter_map(|e| memory_mb(&e.kind)).max() {
+        lines.push(format!("Peak memory: {}", format_mb(peak)));
+    }
+    let outcome = events.iter().rev().find_map(|e| match &e.kind {
+        EventKind::Terminated(t) => Some(termination_text(t)),
+        EventKind::Aborted { reason } => Some(format!(
+            "aborted{}",
+            reason
+                .as_deref()
+                .map(|r| format!(" ({})", r))
+                .unwrap_or_default()
+        )),
+        EventKind::Held { .. } => Some(format!("held: {}", event_details(e))),
+        EventKind::Evicted => Some("evicted".into()),
+        EventKind::Execute { .. } | EventKind::Released { .. } => Some("not finished".into()),
+        _ => None,
+    });
+    if let Some(outcome) = outcome {
+        lines.push(format!("Result:      {}", outcome));
+    }
+    lines
+}
+
+pub fn handle_events(
+    exec: &dyn RemoteExecutor,
+    selector: Option<String>,
+    output: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let username = require_username(exec)?;
+    let attrs = projection(&[
+        JobAttr::Iwd,
+        JobAttr::UserLog,
+        JobAttr::JobStatus,
+        JobAttr::QDate,
+    ]);
+    let job_selector = selector.as_deref().and_then(parse_job_selector);
+    let Some(job) = find_job(exec, &username, &attrs, job_selector, &[])? else {
+        println!("No matching jobs found for user '{}'.", username);
+        return Ok(());
+    };
+
+    let Some(user_log) = job.user_log.as_deref().filter(|l| !l.is_empty()) else {
+        return Err(format!("Job {} has no UserLog", job.id()).into());
+    };
+    let path = build_path(job.iwd.as_deref().unwrap_or("."), user_log);
+    let out = exec.run(&format!("cat '{}'", shell_escape_single_quotes(&path)))?;
+    if !out.success() {
+        let stderr = String::from_utf8_lossy(&out.stderr);
+        return Err(format!("Failed to read user log {}: {}", path, stderr.trim()).into());
+    }
+
+    // Several jobs of a cluster may share one log
+    let events: Vec<UserLogEvent> = parse_user_log(&String::from_utf8_lossy(&out.stdout))
+        .into_iter()
+        .filter(|e| e.cluster_id == job.cluster_id && e.proc_id == job.proc_id)
+        .collect();
+    let rows = timeline(&events);
+
+    if !output.is_table() {
+        print!("{}", event_records(&rows).render(output));
+        return Ok(());
+    }
+
+    println!("Events for job {} ({})", job.id(), path);
+    if rows.is_empty() {
+        println!("No events found.");
+        return Ok(());
+    }
+    println!("{}", render_timeline(&rows));
+    for line in summarize(&events) {
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOG: &str = "\
+000 (7.0.000) 2025-08-08 15:00:00 Job submitted from host: <10.0.0.1:9618>
+...
+000 (7.1.000) 2025-08-08 15:00:00 Job submitted from host: <10.0.0.1:9618>
+...
+001 (7.0.000) 2025-08-08 15:10:00 Job executing on host: <10.0.2.136:9618>
+...
+006 (7.0.000) 2025-08-08 15:20:00 Image size of job updated: 1000
+\t900  -  MemoryUsage of job (MB)
+...
+006 (7.0.000) 2025-08-08 15:30:00 Image size of job updated: 1000
+\t800  -  MemoryUsage of job (MB)
+...
+006 (7.0.000) 2025-08-08 15:40:00 Image size of job updated: 3000
+\t2048  -  MemoryUsage of job (MB)
+...
+005 (7.0.000) 2025-08-08 17:10:00 Job terminated.
+\t(1) Normal termination (return value 1)
+...
+";
+
+    fn job_events() -> Vec<UserLogEvent> {
+        parse_user_log(LOG)
+            .into_iter()
+            .filter(|e| e.proc_id == 0)
+            .collect()
+    }
+
+    #[test]
+    fn timeline_keeps_only_new_memory_peaks() {
+        let events = job_events();
+        let labels: Vec<String> = timeline(&events)
+            .iter()
+            .map(|e| format!("{} {}", event_label(e), event_details(e)))
+            .collect();
+        assert_eq!(
+            labels,
+            vec![
+                "Submitted ",
+                "Executing <10.0.2.136:9618>",
+                "Memory peak 900M",
+                "Memory peak 2.0G",
+                "Terminated exited with code 1"
+            ]
+        );
+    }
+
+    #[test]
+    fn summarizes_wait_runtime_memory_and_result() {
+        assert_eq!(
+            summarize(&job_events()),
+            vec![
+                "Queued:      10m 00s",
+                "Run time:    2h 00m (first start to last event)",
+                "Peak memory: 2.0G",
+                "Result:      exited with code 1",
+            ]
+        );
+    }
+}
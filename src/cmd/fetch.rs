@@ -0,0 +1,198 @@
+use crate::cmd::condor::{projection, JobAd, JobAttr, JobStatus};
+use crate::cmd::logs::{find_jobs, job_files, parse_job_selector, LogFiles};
+use crate::utils::remote::{require_username, RemoteExecutor};
+use crate::utils::ssh::shell_escape_single_quotes;
+use std::path::Path;
+
+/// `find` listing the files under `iwd` that match any of `globs`, as `./relative/path`.
+fn find_command(iwd: &str, globs: &[String]) -> String {
+    let tests: Vec<String> = globs
+        .iter()
+        .map(|g| {
+            format!(
+                "-path './{}'",
+                shell_escape_single_quotes(g.trim_start_matches("./"))
+            )
+        })
+        .collect();
+    format!(
+        "cd '{}' && find . -type f \\( {} \\)",
+        shell_escape_single_quotes(iwd),
+        tests.join(" -o ")
+    )
+}
+
+/// Sources for a relative download: `iwd/./sub/file` keeps `sub/file` below the job directory.
+fn relative_sources(iwd: &str, listing: &str) -> Vec<String> {
+    let mut sources: Vec<String> = listing
+        .lines()
+        .filter_map(|l| l.strip_prefix("./"))
+        .filter(|l| !l.is_empty())
+        .map(|rel| format!("{}/./{}", iwd.trim_end_matches('/'), rel))
+        .collect();
+    sources.sort();
+    sources
+}
+
+/// Copy one batch of files, warning about any that failed; returns whether all arrived.
+fn download_batch(
+    exec: &dyn RemoteExecutor,
+    job: &JobAd,
+    sources: &[String],
+    dest: &Path,
+    relative: bool,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    if sources.is_empty() {
+        return Ok(true);
+    }
+    let out = exec.download(sources, dest, relative)?;
+    if !out.success() {
+        let stderr = String::from_utf8_lossy(&out.stderr);
+        for line in stderr.lines().filter(|l| !l.trim().is_empty()) {
+            eprintln!("Warning: job {}: {}", job.id(), line.trim());
+        }
+    }
+    Ok(out.success())
+}
+
+pub fn handle_fetch(
+    exec: &dyn RemoteExecutor,
+    selector: Option<String>,
+    dest: &Path,
+    globs: &[String],
+    statuses: &[JobStatus],
+) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(g) = globs
+        .iter()
+        .find(|g| g.starts_with('/') || g.contains(".."))
+    {
+        return Err(format!("--glob '{}' must be a path relative to the job's Iwd", g).into());
+    }
+    let username = require_username(exec)?;
+    let attrs = projection(&[
+        JobAttr::Iwd,
+        JobAttr::UserLog,
+        JobAttr::Err,
+        JobAttr::Out,
+        JobAttr::JobStatus,
+        JobAttr::QDate,
+    ]);
+    let job_selector = selector.as_deref().and_then(parse_job_selector);
+    let jobs = find_jobs(exec, &username, &attrs, job_selector, None, statuses)?;
+    if jobs.is_empty() {
+        println!("No matching jobs found for user '{}'.", username);
+        return Ok(());
+    }
+
+    let mut failed = 0;
+    for job in &jobs {
+        let job_dir = dest.join(job.id());
+        std::fs::create_dir_all(&job_dir)
+            .map_err(|e| format!("Failed to create {}: {}", job_dir.display(), e))?;
+
+        let logs: Vec<String> = job_files(job, LogFiles::default())
+            .into_iter()
+            .map(|(_, path)| path)
+            .collect();
+        let mut ok = download_batch(exec, job, &logs, &job_dir, false)?;
+        let mut count = logs.len();
+
+        if !globs.is_empty() {
+            let iwd = job.iwd.as_deref().unwrap_or(".").trim_end_matches('/');
+            let listing = exec.run(&find_command(iwd, globs))?;
+            if !listing.success() {
+                let stderr = String::from_utf8_lossy(&listing.stderr);
+                eprintln!(
+                    "Warning: job {}: could not list {}: {}",
+                    job.id(),
+                    iwd,
+                    stderr.trim()
+                );
+                ok = false;
+            }
+            let matched = relative_sources(iwd, &String::from_utf8_lossy(&listing.stdout));
+            ok &= download_batch(exec, job, &matched, &job_dir, true)?;
+            count += matched.len();
+        }
+
+        println!(
+            "Job {} ({}): {} file{} -> {}{}",
+            job.id(),
+            job.status().label(),
+            count,
+            if count == 1 { "" } else { "s" },
+            job_dir.display(),
+            if ok { "" } else { " (incomplete)" }
+        );
+        if !ok {
+            failed += 1;
+        }
+    }
+
+    if failed > 0 {
+        return Err(format!(
+            "{} of {} job{} incomplete; run the command again to resume",
+            failed,
+            jobs.len(),
+            if jobs.len() == 1 { "" } else { "s" }
+        )
+        .into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::remote::FixtureExecutor;
+
+    #[test]
+    fn lists_glob_matches_relative_to_iwd() {
+        assert_eq!(
+            find_command("/home/me/run 1", &["ckpt/*.pt".into(), "./*.json".into()]),
+            "cd '/home/me/run 1' && find . -type f \\( -path './ckpt/*.pt' -o -path './*.json' \\)"
+        );
+        assert_eq!(
+            relative_sources("/home/me/run/", "./ckpt/last.pt\n./a.json\n"),
+            vec!["/home/me/run/./a.json", "/home/me/run/./ckpt/last.pt"]
+        );
+    }
+
+    #[test]
+    fn downloads_logs_and_globbed_files_per_job() {
+        let attrs = "ClusterId,ProcId,Iwd,UserLog,Err,Out,JobStatus,QDate";
+        let iwd = "/lustre/home/barmstrong/ligoss";
+        let exec = FixtureExecutor::new("barmstrong")
+            .with_stdout(
+                &format!(
+                    "condor_q barmstrong -json -attributes {} -constraint 'ClusterId == 16337546 && ProcId == 0'",
+                    attrs
+                ),
+                include_str!("../../example.json"),
+            )
+            .with_stdout(
+                &find_command(iwd, &["*.pt".into()]),
+                "./model.pt\n",
+            );
+        let dest = std::env::temp_dir().join(format!("mct-fetch-test-{}", std::process::id()));
+        handle_fetch(
+            &exec,
+            Some("16337546.0".into()),
+            &dest,
+            &["*.pt".into()],
+            &[],
+        )
+        .unwrap();
+        assert!(dest.join("16337546.0").is_dir());
+        std::fs::remove_dir_all(&dest).unwrap();
+
+        let downloads: Vec<String> = exec
+            .calls()
+            .into_iter()
+            .filter(|c| c.starts_with("download"))
+            .collect();
+        assert_eq!(downloads.len(), 2);
+        assert!(downloads[0].ends_with(&format!("-> {}", dest.join("16337546.0").display())));
+        assert!(downloads[1].starts_with(&format!("download -R {}/./model.pt", iwd)));
+    }
+}
@@ -0,0 +1,271 @@
+use crate::cmd::condor::{projection, JobAd, JobAttr, JobStatus};
+use crate::cmd::logs::{find_jobs, job_files, parse_job_selector, LogFiles};
+use crate::utils::remote::{require_username, CommandOutput, RemoteExecutor};
+use crate::utils::ssh::shell_escape_single_quotes;
+
+/// What to search for in `mct logs --grep`.
+#[derive(Debug, Clone)]
+pub struct GrepOptions {
+    /// Extended regular expression, as understood by `grep -E`
+    pub pattern: String,
+    pub ignore_case: bool,
+    /// Lines of context around each match
+    pub context: usize,
+}
+
+/// A line of `grep -H -n` output attributed to one of the searched files.
+#[derive(Debug, Clone, PartialEq)]
+struct GrepLine {
+    path: String,
+    line_no: u64,
+    is_match: bool,
+    text: String,
+}
+
+fn grep_command(opts: &GrepOptions, paths: &[&str]) -> String {
+    // -s: missing files are expected for jobs that never wrote them
+    let mut cmd = String::from("grep -E -H -n -s");
+    if opts.ignore_case {
+        cmd.push_str(" -i");
+    }
+    if opts.context > 0 {
+        cmd.push_str(&format!(" -C {}", opts.context));
+    }
+    cmd.push_str(&format!(
+        " -e '{}' --",
+        shell_escape_single_quotes(&opts.pattern)
+    ));
+    for path in paths {
+        cmd.push_str(&format!(" '{}'", shell_escape_single_quotes(path)));
+    }
+    cmd
+}
+
+/// grep exits with 1 for no matches and 2 for errors. Under `-s` a missing file
+/// still yields 2 but no message, so only errors with a message (e.g. an invalid
+/// pattern) count as failures, and only if nothing was found in the other files.
+fn check_grep_output(out: &CommandOutput) -> Result<(), String> {
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    match out.code {
+        Some(code) if code > 1 && out.stdout.is_empty() && !stderr.trim().is_empty() => {
+            Err(format!("grep failed: {}", stderr.trim()))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Parse `path:N:text` (match) and `path-N-text` (context) lines. Paths are
+/// matched against the known file list since they may contain `:` or `-`.
+fn parse_grep_output(output: &str, paths: &[&str]) -> Vec<GrepLine> {
+    let mut by_length: Vec<&str> = paths.to_vec();
+    by_length.sort_by_key(|p| std::cmp::Reverse(p.len()));
+
+    let mut lines = Vec::new();
+    for line in output.lines() {
+        for path in &by_length {
+            let Some(rest) = line.strip_prefix(path) else {
+                continue;
+            };
+            let mut chars = rest.chars();
+            let is_match = match chars.next() {
+                Some(':') => true,
+                Some('-') => false,
+                _ => continue,
+            };
+            let rest = chars.as_str();
+            let digits = rest.chars().take_while(|c| c.is_ascii_digit()).count();
+            let Ok(line_no) = rest[..digits].parse::<u64>() else {
+                continue;
+            };
+            let text = rest[digits..].get(1..).unwrap_or("").to_string();
+            lines.push(GrepLine {
+                path: path.to_string(),
+                line_no,
+                is_match,
+                text,
+            });
+            break;
+        }
+    }
+    lines
+}
+
+pub fn handle_log_grep(
+    exec: &dyn RemoteExecutor,
+    selector: Option<String>,
+    last: Option<usize>,
+    files: LogFiles,
+    statuses: &[JobStatus],
+    opts: &GrepOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let username = require_username(exec)?;
+    let attrs = projection(&[
+        JobAttr::Iwd,
+        JobAttr::UserLog,
+        JobAttr::Err,
+        JobAttr::Out,
+        JobAttr::JobStatus,
+        JobAttr::QDate,
+    ]);
+    let job_selector = selector.as_deref().and_then(parse_job_selector);
+    let jobs = find_jobs(exec, &username, &attrs, job_selector, last, statuses)?;
+    if jobs.is_empty() {
+        println!("No matching jobs found for user '{}'.", username);
+        return Ok(());
+    }
+
+    let job_files: Vec<(&JobAd, Vec<(String, String)>)> =
+        jobs.iter().map(|j| (j, job_files(j, files))).collect();
+    let mut paths: Vec<&str> = job_files
+        .iter()
+        .flat_map(|(_, f)| f.iter().map(|(_, p)| p.as_str()))
+        .collect();
+    paths.sort();
+    paths.dedup();
+    if paths.is_empty() {
+        println!("No log files set for the selected jobs.");
+        return Ok(());
+    }
+
+    // One remote grep over all files
+    let out = exec.run(&grep_command(opts, &paths))?;
+    check_grep_output(&out)?;
+    let lines = parse_grep_output(&String::from_utf8_lossy(&out.stdout), &paths);
+
+    let mut jobs_with_matches = 0;
+    let mut total_matches = 0;
+    for (job, files) in &job_files {
+        let mut printed_header = false;
+        for (label, path) in files {
+            let file_lines: Vec<&GrepLine> = lines.iter().filter(|l| l.path == *path).collect();
+            if !file_lines.iter().any(|l| l.is_match) {
+                continue;
+            }
+            if !printed_header {
+                println!("\n== Job {} ({})", job.id(), job.status().label());
+                printed_header = true;
+                jobs_with_matches += 1;
+            }
+            println!("[{}] {}", label, path);
+            let mut prev_line: Option<u64> = None;
+            for l in file_lines {
+                // Separate non-adjacent context groups like grep's `--`
+                if prev_line.is_some_and(|p| l.line_no > p + 1) {
+                    println!("  --");
+                }
+                let sep = if l.is_match { ':' } else { '-' };
+                println!("{:>7}{} {}", l.line_no, sep, l.text);
+                prev_line = Some(l.line_no);
+                if l.is_match {
+                    total_matches += 1;
+                }
+            }
+        }
+    }
+
+    println!(
+        "\n{} matching line{} in {} of {} job{}.",
+        total_matches,
+        if total_matches == 1 { "" } else { "s" },
+        jobs_with_matches,
+        jobs.len(),
+        if jobs.len() == 1 { "" } else { "s" }
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::remote::{FixtureExecutor, LocalExecutor};
+
+    #[test]
+    fn builds_remote_grep_command() {
+        let opts = GrepOptions {
+            pattern: "CUDA out of memory|it's".into(),
+            ignore_case: true,
+            context: 2,
+        };
+        assert_eq!(
+            grep_command(&opts, &["/o.txt", "/e.txt"]),
+            "grep -E -H -n -s -i -C 2 -e 'CUDA out of memory|it'\\''s' -- '/o.txt' '/e.txt'"
+        );
+    }
+
+    #[test]
+    fn reports_grep_errors_unless_something_matched() {
+        let output = |code: i32, stdout: &str, stderr: &str| CommandOutput {
+            code: Some(code),
+            stdout: stdout.as_bytes().to_vec(),
+            stderr: stderr.as_bytes().to_vec(),
+        };
+        assert_eq!(check_grep_output(&output(1, "", "")), Ok(()));
+        assert_eq!(
+            check_grep_output(&output(2, "", "grep: Unmatched ( or \\(\n")),
+            Err("grep failed: grep: Unmatched ( or \\(".to_string())
+        );
+        assert_eq!(check_grep_output(&output(2, "", "")), Ok(()));
+        assert_eq!(check_grep_output(&output(2, "/o.txt:1:boom\n", "")), Ok(()));
+    }
+
+    #[test]
+    fn missing_log_files_mean_no_matches() {
+        let dir = std::env::temp_dir().join(format!("mct-grep-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let out_path = dir.join("out.txt");
+        std::fs::write(&out_path, "step 1\nstep 2\n").unwrap();
+        let err_path = dir.join("err.txt");
+        let opts = GrepOptions {
+            pattern: "Traceback".into(),
+            ignore_case: false,
+            context: 0,
+        };
+        let paths = [out_path.to_str().unwrap(), err_path.to_str().unwrap()];
+        let out = LocalExecutor.run(&grep_command(&opts, &paths)).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        // The never-written err.txt makes grep exit with 2, silently
+        assert_eq!(out.code, Some(2));
+        assert!(out.stderr.is_empty());
+        assert_eq!(check_grep_output(&out), Ok(()));
+    }
+
+    #[test]
+    fn parses_matches_and_context_with_awkward_paths() {
+        let paths = ["/runs/a-1/out.txt", "/runs/a-1/out.txt.1"];
+        let output = "/runs/a-1/out.txt-9-step 9\n/runs/a-1/out.txt:10:Traceback: boom\n--\n/runs/a-1/out.txt.1:3:Traceback\n";
+        let lines = parse_grep_output(output, &paths);
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            lines[1],
+            GrepLine {
+                path: "/runs/a-1/out.txt".into(),
+                line_no: 10,
+                is_match: true,
+                text: "Traceback: boom".into()
+            }
+        );
+        assert!(!lines[0].is_match);
+        assert_eq!(lines[2].path, "/runs/a-1/out.txt.1");
+    }
+
+    #[test]
+    fn searches_last_history_entries() {
+        let attrs = "ClusterId,ProcId,Iwd,UserLog,Err,Out,JobStatus,QDate";
+        let exec = FixtureExecutor::new("barmstrong").with_stdout(
+            &format!(
+                "condor_history barmstrong -json -attributes {} -limit 5",
+                attrs
+            ),
+            include_str!("../../example.json"),
+        );
+        let jobs = find_jobs(&exec, "barmstrong", attrs, None, Some(5), &[]).unwrap();
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(
+            job_files(&jobs[0], LogFiles::default())
+                .iter()
+                .map(|(l, _)| l.as_str())
+                .collect::<Vec<_>>(),
+            vec!["Log", "Out", "Err"]
+        );
+    }
+}
@@ -1,43 +1,13 @@
-use crate::cmd::condor::condor_history_for_user;
-use crate::config::ClusterConfig;
-use crate::utils::serde::{deserialize_i64_lenient, deserialize_request_gpus};
+use crate::cmd::columns::format_unix;
+use crate::cmd::condor::{condor_history_for_user, projection, JobAd, JobAttr};
+use crate::utils::output::{OutputFormat, Records};
+use crate::utils::remote::{require_username, RemoteExecutor};
 use comfy_table::{
     presets::UTF8_FULL, Attribute, Cell, CellAlignment, Color, ContentArrangement, Table,
 };
-use serde::Deserialize;
-use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+use serde_json::json;
 
-#[derive(Deserialize, Debug)]
-struct HistRow {
-    #[serde(rename = "ClusterId")]
-    cluster_id: i64,
-    #[serde(rename = "ProcId")]
-    proc_id: i64,
-    #[serde(rename = "Cmd")]
-    cmd: Option<String>,
-    #[serde(rename = "Args")]
-    args: Option<String>,
-    #[serde(
-        rename = "RequestGPUs",
-        default,
-        deserialize_with = "deserialize_request_gpus"
-    )]
-    request_gpus: i32,
-    #[serde(
-        rename = "QDate",
-        default,
-        deserialize_with = "deserialize_i64_lenient"
-    )]
-    q_unix: i64,
-    #[serde(
-        rename = "JobStartDate",
-        default,
-        deserialize_with = "deserialize_i64_lenient"
-    )]
-    start_unix: i64,
-}
-
-fn render_hist_table(rows: &[HistRow]) -> Table {
+fn render_hist_table(rows: &[JobAd]) -> Table {
     let mut table = Table::new();
     table
         .load_preset(UTF8_FULL)
@@ -58,27 +28,14 @@ fn render_hist_table(rows: &[HistRow]) -> Table {
         ]);
 
     for j in rows {
-        let jobid = format!("{}.{}", j.cluster_id, j.proc_id);
-        let queued = if j.q_unix > 0 {
-            // QDate is seconds since epoch
-            let dt = OffsetDateTime::from_unix_timestamp(j.q_unix).ok();
-            dt.map(|d| d.format(&Rfc3339).unwrap_or_else(|_| "-".into()))
-                .unwrap_or_else(|| "-".into())
-        } else {
-            "-".into()
-        };
-        let started = if j.start_unix > 0 {
-            let dt = OffsetDateTime::from_unix_timestamp(j.start_unix).ok();
-            dt.map(|d| d.format(&Rfc3339).unwrap_or_else(|_| "-".into()))
-                .unwrap_or_else(|| "-".into())
-        } else {
-            "-".into()
-        };
+        let jobid = j.id();
+        let queued = format_unix(j.q_date);
+        let started = format_unix(j.job_start_date);
         table.add_row(vec![
             Cell::new(jobid).fg(Color::DarkGrey),
             Cell::new(j.cmd.as_deref().unwrap_or("")),
             Cell::new(j.args.as_deref().unwrap_or("")),
-            Cell::new(j.request_gpus.to_string()).set_alignment(CellAlignment::Right),
+            Cell::new(j.gpus().to_string()).set_alignment(CellAlignment::Right),
             Cell::new(queued).set_alignment(CellAlignment::Right),
             Cell::new(started).set_alignment(CellAlignment::Right),
         ]);
@@ -87,37 +44,55 @@ fn render_hist_table(rows: &[HistRow]) -> Table {
     table
 }
 
-pub fn handle_hist(limit: Option<usize>) -> Result<(), Box<dyn std::error::Error>> {
-    let config = ClusterConfig::load();
-    let login = match &config.login {
-        Some(l) => l,
-        None => {
-            eprintln!("Error: No login configuration found. Run 'mct login' first.");
-            std::process::exit(1);
-        }
-    };
+fn hist_records(rows: &[JobAd]) -> Records {
+    let mut records = Records::new(vec![
+        "job_id",
+        "cluster_id",
+        "proc_id",
+        "cmd",
+        "args",
+        "gpus",
+        "q_date",
+        "job_start_date",
+    ]);
+    for j in rows {
+        records.push(vec![
+            json!(j.id()),
+            json!(j.cluster_id),
+            json!(j.proc_id),
+            json!(j.cmd),
+            json!(j.args),
+            json!(j.gpus()),
+            json!(j.q_date),
+            json!(j.job_start_date),
+        ]);
+    }
+    records
+}
 
-    let username = config.get_username().ok_or_else(|| {
-        std::io::Error::new(
-            std::io::ErrorKind::Other,
-            "No username found in login or ssh config",
-        )
-    })?;
+pub fn handle_hist(
+    exec: &dyn RemoteExecutor,
+    limit: Option<usize>,
+    output: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let username = require_username(exec)?;
 
     let limit = limit.unwrap_or(10);
 
-    let attrs = [
-        "ClusterId",
-        "ProcId",
-        "Cmd",
-        "Args",
-        "RequestGPUs",
-        "QDate",
-        "JobStartDate",
-    ]
-    .join(",");
+    let attrs = projection(&[
+        JobAttr::Cmd,
+        JobAttr::Args,
+        JobAttr::RequestGpus,
+        JobAttr::QDate,
+        JobAttr::JobStartDate,
+    ]);
 
-    let rows: Vec<HistRow> = condor_history_for_user(login, &username, &attrs, limit)?;
+    let rows: Vec<JobAd> = condor_history_for_user(exec, &username, &attrs, limit, None)?;
+
+    if !output.is_table() {
+        print!("{}", hist_records(&rows).render(output));
+        return Ok(());
+    }
 
     if rows.is_empty() {
         println!("No historical jobs found for user {}.", username);
@@ -128,3 +103,37 @@ pub fn handle_hist(limit: Option<usize>) -> Result<(), Box<dyn std::error::Error
     println!("{}", table);
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::remote::FixtureExecutor;
+
+    #[test]
+    fn records_from_captured_history() {
+        let attrs = "ClusterId,ProcId,Cmd,Args,RequestGPUs,QDate,JobStartDate";
+        let exec = FixtureExecutor::new("barmstrong").with_stdout(
+            &format!(
+                "condor_history barmstrong -json -attributes {} -limit 10",
+                attrs
+            ),
+            include_str!("../../example.json"),
+        );
+        let rows = condor_history_for_user(&exec, "barmstrong", attrs, 10, None).unwrap();
+        let csv = hist_records(&rows).render(OutputFormat::Csv);
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next(),
+            Some("job_id,cluster_id,proc_id,cmd,args,gpus,q_date,job_start_date")
+        );
+        let row = lines.next().unwrap();
+        assert!(row.starts_with(
+            "16337546.0,16337546,0,/home/barmstrong/cluster/htcondor/cuda_wrapper.sh,"
+        ));
+        assert!(row.ends_with(",1,1754665416,1754665417"));
+        assert_eq!(lines.next(), None);
+        assert!(render_hist_table(&rows)
+            .to_string()
+            .contains("2025-08-08T15:03:36Z"));
+    }
+}
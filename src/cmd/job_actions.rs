@@ -0,0 +1,262 @@
+use crate::cmd::condor::{price_to_job_prio, JobStatus};
+use crate::utils::remote::RemoteExecutor;
+
+/// Highest bid accepted by `JobPrio` (see [`crate::cmd::condor::job_prio_to_price`]).
+pub const MAX_BID: f64 = 2000.0;
+
+/// A change to queued jobs, run with the HTCondor tool of the same name.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JobAction {
+    Remove,
+    Hold,
+    Release,
+    /// Change the bid via `condor_prio`
+    SetBid(f64),
+}
+
+impl JobAction {
+    /// Imperative description for confirmation prompts, e.g. "Remove job 1.0".
+    pub fn describe(&self, target: &str) -> String {
+        match self {
+            JobAction::Remove => format!("Remove {}", target),
+            JobAction::Hold => format!("Hold {}", target),
+            JobAction::Release => format!("Release {}", target),
+            JobAction::SetBid(bid) => format!("Set bid of {} to {}", target, bid),
+        }
+    }
+
+    /// e.g. "removed", for messages about jobs the action does not apply to.
+    pub fn past_participle(&self) -> &'static str {
+        match self {
+            JobAction::Remove => "removed",
+            JobAction::Hold => "held",
+            JobAction::Release => "released",
+            JobAction::SetBid(_) => "re-prioritised",
+        }
+    }
+
+    pub fn command(&self, ids: &[String]) -> String {
+        let ids = ids.join(" ");
+        match self {
+            JobAction::Remove => format!("condor_rm {}", ids),
+            JobAction::Hold => format!("condor_hold {}", ids),
+            JobAction::Release => format!("condor_release {}", ids),
+            JobAction::SetBid(bid) => {
+                format!("condor_prio -p {} {}", price_to_job_prio(*bid) as i64, ids)
+            }
+        }
+    }
+
+    /// Why the action cannot apply to a job in `status`, if it cannot.
+    pub fn unavailable_reason(&self, status: JobStatus) -> Option<&'static str> {
+        match (self, status) {
+            (_, JobStatus::Completed | JobStatus::Removed) => Some("is no longer in the queue"),
+            (JobAction::Hold, JobStatus::Held) => Some("is already held"),
+            (JobAction::Release, s) if s != JobStatus::Held => Some("is not held"),
+            _ => None,
+        }
+    }
+}
+
+/// Parse a bid typed by the user, rejecting values outside the `JobPrio` range.
+pub fn parse_bid(input: &str) -> Result<f64, String> {
+    let bid: f64 = input
+        .trim()
+        .parse()
+        .map_err(|_| format!("'{}' is not a number", input.trim()))?;
+    if !(0.0..=MAX_BID).contains(&bid) || bid.fract() != 0.0 {
+        return Err(format!("Bid must be a whole number from 0 to {}", MAX_BID));
+    }
+    Ok(bid)
+}
+
+/// "job 1.0" or "3 jobs", for prompts and summaries.
+pub fn jobs_label(ids: &[String]) -> String {
+    match ids {
+        [id] => format!("job {}", id),
+        _ => format!("{} jobs", ids.len()),
+    }
+}
+
+/// Per-job outcome of one batched action.
+#[derive(Debug, Default, PartialEq)]
+pub struct ActionReport {
+    pub succeeded: Vec<String>,
+    /// Job id and the tool's message about it
+    pub failed: Vec<(String, String)>,
+}
+
+impl ActionReport {
+    pub fn all_succeeded(&self) -> bool {
+        self.failed.is_empty()
+    }
+
+    pub fn summary(&self, action: &JobAction) -> String {
+        match (self.succeeded.as_slice(), self.failed.as_slice()) {
+            ([], [(id, message)]) => format!("Job {}: {}", id, message),
+            ([id], []) => format!("{}: done", action.describe(&format!("job {}", id))),
+            (ok, []) => format!(
+                "{}: all {} succeeded",
+                action.describe(&format!("{} jobs", ok.len())),
+                ok.len()
+            ),
+            (ok, failed) => {
+                let details: Vec<String> = failed
+                    .iter()
+                    .map(|(id, message)| format!("{}: {}", id, message))
+                    .collect();
+                format!(
+                    "{}: {} succeeded, {} failed ({})",
+                    action.describe(&format!("{} jobs", ok.len() + failed.len())),
+                    ok.len(),
+                    failed.len(),
+                    details.join("; ")
+                )
+            }
+        }
+    }
+}
+
+// condor_rm/hold/release print `Job 1.0 marked for removal`, `Job 1.0 not found`, ...
+fn is_failure(line: &str) -> bool {
+    let line = line.to_ascii_lowercase();
+    [
+        "not ",
+        "couldn't",
+        "could not",
+        "fail",
+        "invalid",
+        "error",
+        "already",
+    ]
+    .iter()
+    .any(|marker| line.contains(marker))
+}
+
+/// Attribute the tool's output lines to `ids`. Jobs not mentioned succeeded
+/// if the command did; otherwise they fail with the unattributed output.
+fn classify_output(ids: &[String], success: bool, output: &str) -> ActionReport {
+    let lines: Vec<&str> = output
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .collect();
+    let mentions = |line: &str, id: &str| {
+        line.split(|c: char| c.is_whitespace() || c == ',' || c == ':')
+            .any(|word| word == id)
+    };
+    let mut report = ActionReport::default();
+    for id in ids {
+        match lines.iter().find(|l| mentions(l, id)) {
+            Some(line) if is_failure(line) => report.failed.push((id.clone(), line.to_string())),
+            Some(_) => report.succeeded.push(id.clone()),
+            None if success => report.succeeded.push(id.clone()),
+            None => report.failed.push((id.clone(), String::new())),
+        }
+    }
+    // e.g. "Couldn't find/remove all jobs specified."
+    let fallback = lines
+        .iter()
+        .filter(|l| !ids.iter().any(|id| mentions(l, id)))
+        .copied()
+        .collect::<Vec<_>>()
+        .join("; ");
+    for (_, message) in report.failed.iter_mut().filter(|(_, m)| m.is_empty()) {
+        *message = if fallback.is_empty() {
+            "command failed".to_string()
+        } else {
+            fallback.clone()
+        };
+    }
+    report
+}
+
+/// Run `action` on all `ids` with one remote command and report per-job results.
+pub fn run_action(exec: &dyn RemoteExecutor, action: &JobAction, ids: &[String]) -> ActionReport {
+    match exec.run(&action.command(ids)) {
+        Ok(out) => {
+            let output = format!(
+                "{}\n{}",
+                String::from_utf8_lossy(&out.stdout),
+                String::from_utf8_lossy(&out.stderr)
+            );
+            classify_output(ids, out.success(), &output)
+        }
+        Err(e) => ActionReport {
+            failed: ids.iter().map(|id| (id.clone(), e.to_string())).collect(),
+            ..Default::default()
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::remote::{CommandOutput, FixtureExecutor};
+
+    #[test]
+    fn builds_commands_and_checks_status() {
+        let ids = vec!["12.0".to_string(), "12.1".to_string()];
+        assert_eq!(JobAction::Remove.command(&ids), "condor_rm 12.0 12.1");
+        assert_eq!(
+            JobAction::SetBid(250.0).command(&ids[..1]),
+            "condor_prio -p -750 12.0"
+        );
+        assert_eq!(
+            JobAction::Release.unavailable_reason(JobStatus::Running),
+            Some("is not held")
+        );
+        assert_eq!(JobAction::Remove.unavailable_reason(JobStatus::Held), None);
+        assert!(parse_bid("2500").is_err());
+        assert_eq!(parse_bid(" 300 "), Ok(300.0));
+    }
+
+    #[test]
+    fn reports_single_job_results() {
+        let exec = FixtureExecutor::new("barmstrong")
+            .with_stdout("condor_hold 12.0", "Job 12.0 held\n")
+            .with_output(
+                "condor_release 12.0",
+                CommandOutput {
+                    code: Some(1),
+                    stdout: Vec::new(),
+                    stderr: b"Job 12.0 not held to be released\n".to_vec(),
+                },
+            );
+        let ids = vec!["12.0".to_string()];
+        assert_eq!(
+            run_action(&exec, &JobAction::Hold, &ids).summary(&JobAction::Hold),
+            "Hold job 12.0: done"
+        );
+        assert_eq!(
+            run_action(&exec, &JobAction::Release, &ids).summary(&JobAction::Release),
+            "Job 12.0: Job 12.0 not held to be released"
+        );
+    }
+
+    #[test]
+    fn summarizes_batched_results_per_job() {
+        let ids: Vec<String> = ["12.0", "12.1", "12.2"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let exec = FixtureExecutor::new("barmstrong").with_output(
+            "condor_rm 12.0 12.1 12.2",
+            CommandOutput {
+                code: Some(1),
+                stdout: b"Job 12.0 marked for removal\nJob 12.2 marked for removal\n".to_vec(),
+                stderr: b"Job 12.1 not found\nCouldn't find/remove all jobs specified.\n".to_vec(),
+            },
+        );
+        let report = run_action(&exec, &JobAction::Remove, &ids);
+        assert_eq!(report.succeeded, vec!["12.0", "12.2"]);
+        assert_eq!(
+            report.failed,
+            vec![("12.1".to_string(), "Job 12.1 not found".to_string())]
+        );
+        assert_eq!(
+            report.summary(&JobAction::Remove),
+            "Remove 3 jobs: 2 succeeded, 1 failed (12.1: Job 12.1 not found)"
+        );
+    }
+}
@@ -0,0 +1,235 @@
+use crate::cmd::columns::{format_mb, format_unix};
+use crate::cmd::condor::{job_prio_to_price, JobAttr};
+use crate::utils::remote::RemoteExecutor;
+use std::collections::BTreeMap;
+
+/// A job ClassAd as printed by `condor_q -l`: attribute name to value, with
+/// string values unquoted and other values (numbers, expressions) verbatim.
+pub type LongAd = BTreeMap<String, String>;
+
+/// Parse the first ad of `-l` output (`Name = value` lines, ads separated by blank lines).
+pub fn parse_long_ad(text: &str) -> LongAd {
+    let mut ad = LongAd::new();
+    for line in text.lines() {
+        if line.trim().is_empty() {
+            if ad.is_empty() {
+                continue;
+            }
+            break;
+        }
+        let Some((name, value)) = line.split_once(" = ") else {
+            continue;
+        };
+        ad.insert(name.trim().to_string(), unquote(value.trim()));
+    }
+    ad
+}
+
+fn unquote(value: &str) -> String {
+    match value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+        Some(inner) => inner.replace("\\\"", "\"").replace("\\\\", "\\"),
+        None => value.to_string(),
+    }
+}
+
+/// Fetch the full ad of `job_id` from the queue or, if it has left the queue
+/// (or `finished` is set), from `condor_history`.
+pub fn fetch_long_ad(
+    exec: &dyn RemoteExecutor,
+    job_id: &str,
+    finished: bool,
+) -> Result<LongAd, String> {
+    let run = |cmd: String| -> Result<LongAd, String> {
+        let out = exec.run(&cmd).map_err(|e| e.to_string())?;
+        if !out.success() {
+            let stderr = String::from_utf8_lossy(&out.stderr);
+            return Err(format!("{} failed: {}", cmd, stderr.trim()));
+        }
+        Ok(parse_long_ad(&String::from_utf8_lossy(&out.stdout)))
+    };
+    if !finished {
+        let ad = run(format!("condor_q {} -l", job_id))?;
+        if !ad.is_empty() {
+            return Ok(ad);
+        }
+    }
+    let ad = run(format!("condor_history {} -l -limit 1", job_id))?;
+    if ad.is_empty() {
+        return Err(format!("Job {} not found in queue or history", job_id));
+    }
+    Ok(ad)
+}
+
+fn int(ad: &LongAd, attr: JobAttr) -> Option<i64> {
+    ad.get(attr.name())
+        .and_then(|v| v.parse::<f64>().ok())
+        .map(|v| v as i64)
+}
+
+fn text(ad: &LongAd, attr: JobAttr) -> Option<&str> {
+    ad.get(attr.name())
+        .map(|v| v.as_str())
+        .filter(|v| !v.is_empty() && *v != "undefined")
+}
+
+fn pair(
+    requested: Option<i64>,
+    provisioned: Option<i64>,
+    fmt: fn(i64) -> String,
+) -> Option<String> {
+    if requested.is_none() && provisioned.is_none() {
+        return None;
+    }
+    let show = |v: Option<i64>| v.map(fmt).unwrap_or_else(|| "-".to_string());
+    Some(format!("{} / {}", show(requested), show(provisioned)))
+}
+
+/// Labelled summary of the attributes most useful when inspecting a job.
+pub fn summary_lines(ad: &LongAd) -> Vec<(&'static str, String)> {
+    let mut lines: Vec<(&'static str, Option<String>)> = vec![
+        (
+            "Host",
+            text(ad, JobAttr::RemoteHost)
+                .or_else(|| ad.get("LastRemoteHost").map(|s| s.as_str()))
+                .map(str::to_string),
+        ),
+        (
+            "Assigned GPUs",
+            text(ad, JobAttr::AssignedGpus).map(str::to_string),
+        ),
+        (
+            "CPUs req/prov",
+            pair(
+                int(ad, JobAttr::RequestCpus),
+                int(ad, JobAttr::CpusProvisioned),
+                |v| v.to_string(),
+            ),
+        ),
+        (
+            "GPUs req/prov",
+            pair(
+                int(ad, JobAttr::RequestGpus),
+                int(ad, JobAttr::GpusProvisioned),
+                |v| v.to_string(),
+            ),
+        ),
+        (
+            "Memory req/prov",
+            pair(
+                int(ad, JobAttr::RequestMemory),
+                int(ad, JobAttr::MemoryProvisioned),
+                format_mb,
+            ),
+        ),
+        (
+            "Bid",
+            int(ad, JobAttr::JobPrio).map(|p| job_prio_to_price(p).to_string()),
+        ),
+        (
+            "Queued",
+            int(ad, JobAttr::QDate).map(|t| format_unix(Some(t))),
+        ),
+        (
+            "Started",
+            int(ad, JobAttr::JobCurrentStartDate)
+                .or(int(ad, JobAttr::JobStartDate))
+                .map(|t| format_unix(Some(t))),
+        ),
+        (
+            "Completed",
+            int(ad, JobAttr::CompletionDate)
+                .filter(|t| *t > 0)
+                .map(|t| format_unix(Some(t))),
+        ),
+    ];
+    let exit = if text(ad, JobAttr::ExitBySignal) == Some("true") {
+        int(ad, JobAttr::ExitSignal).map(|s| format!("signal {}", s))
+    } else {
+        int(ad, JobAttr::ExitCode).map(|c| c.to_string())
+    };
+    lines.push(("Exit", exit));
+    lines.push((
+        "Hold reason",
+        text(ad, JobAttr::HoldReason).map(|r| match int(ad, JobAttr::HoldReasonCode) {
+            Some(code) => format!("[{}] {}", code, r),
+            None => r.to_string(),
+        }),
+    ));
+    for (label, attr) in [
+        ("Iwd", JobAttr::Iwd),
+        ("Out", JobAttr::Out),
+        ("Err", JobAttr::Err),
+        ("UserLog", JobAttr::UserLog),
+    ] {
+        lines.push((label, text(ad, attr).map(str::to_string)));
+    }
+    lines
+        .into_iter()
+        .filter_map(|(label, value)| value.map(|v| (label, v)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::remote::FixtureExecutor;
+
+    const AD: &str = "\
+Args = \"train.py --name \\\"big run\\\"\"
+AssignedGPUs = \"GPU-5d3b\"
+ClusterId = 12
+CpusProvisioned = 8
+ExitBySignal = false
+HoldReason = undefined
+JobPrio = -750
+QDate = 1754658216
+RemoteHost = \"slot1_1@gpu-node-3\"
+RequestCpus = 8
+RequestMemory = 65536
+Requirements = (TARGET.Arch == \"X86_64\") && (TARGET.GPUs >= RequestGPUs)
+
+ClusterId = 11
+";
+
+    #[test]
+    fn parses_first_ad_and_unquotes_strings() {
+        let ad = parse_long_ad(AD);
+        assert_eq!(ad["ClusterId"], "12");
+        assert_eq!(ad["Args"], "train.py --name \"big run\"");
+        assert_eq!(
+            ad["Requirements"],
+            "(TARGET.Arch == \"X86_64\") && (TARGET.GPUs >= RequestGPUs)"
+        );
+    }
+
+    #[test]
+    fn summarizes_known_attributes() {
+        let lines = summary_lines(&parse_long_ad(AD));
+        let labels: Vec<&str> = lines.iter().map(|(l, _)| *l).collect();
+        assert_eq!(
+            labels,
+            vec![
+                "Host",
+                "Assigned GPUs",
+                "CPUs req/prov",
+                "Memory req/prov",
+                "Bid",
+                "Queued"
+            ]
+        );
+        assert_eq!(lines[3].1, "64.0G / -");
+        assert_eq!(lines[4].1, "250");
+    }
+
+    #[test]
+    fn falls_back_to_history() {
+        let exec = FixtureExecutor::new("barmstrong")
+            .with_stdout("condor_q 12.0 -l", "")
+            .with_stdout("condor_history 12.0 -l -limit 1", AD);
+        assert_eq!(
+            fetch_long_ad(&exec, "12.0", false).unwrap()["JobPrio"],
+            "-750"
+        );
+        assert_eq!(exec.calls().len(), 2);
+    }
+}
@@ -1,43 +1,22 @@
-use crate::cmd::condor::condor_history_for_user;
-use crate::cmd::logs::handle_logs;
-use crate::config::ClusterConfig;
-use crate::utils::serde::deserialize_request_gpus;
-use crate::utils::ssh::{parse_json_relaxed, run_remote};
+use crate::cmd::condor::{condor_history_for_user, projection, JobAd, JobAttr};
+use crate::cmd::job_actions::{jobs_label, parse_bid, run_action, ActionReport, JobAction};
+use crate::cmd::job_detail::{fetch_long_ad, summary_lines, LongAd};
+use crate::cmd::log_view::{fetch_tail, LogView, LogViewKey, TailChunk};
+use crate::utils::launcher::Launcher;
+use crate::utils::remote::{require_username, RemoteExecutor};
+use crate::utils::ssh::parse_json_relaxed;
+use crate::utils::time::human_duration;
 use crossterm::event::{read, Event, KeyCode, KeyEvent, KeyModifiers};
 use crossterm::{
     cursor, execute,
     style::{Color, ResetColor, SetForegroundColor},
     terminal,
 };
-use serde::Deserialize;
+use std::collections::{BTreeSet, HashMap};
 use std::io::{stdout, Write};
-use std::process::Command;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
 use std::time::{Duration, Instant};
 
-#[derive(Deserialize, Debug)]
-struct JobRow {
-    #[serde(rename = "ClusterId")]
-    cluster_id: i64,
-    #[serde(rename = "ProcId")]
-    proc_id: i64,
-    #[serde(rename = "Cmd")]
-    cmd: Option<String>,
-    #[serde(rename = "Args")]
-    args: Option<String>,
-    #[serde(rename = "JobStatus")]
-    job_status: i32,
-    #[serde(
-        rename = "RequestGPUs",
-        default,
-        deserialize_with = "deserialize_request_gpus"
-    )]
-    request_gpus: i32,
-}
-
-fn job_id(j: &JobRow) -> String {
-    format!("{}.{}", j.cluster_id, j.proc_id)
-}
-
 fn scrolling_window(text: &str, width: usize, offset: usize) -> String {
     let mut chars: Vec<char> = text.chars().collect();
     if width == 0 {
@@ -50,7 +29,7 @@ fn scrolling_window(text: &str, width: usize, offset: usize) -> String {
     let gap = std::cmp::min(width, 10);
     let mut ext: Vec<char> = Vec::with_capacity(chars.len() * 2 + gap);
     ext.extend_from_slice(&chars);
-    ext.extend(std::iter::repeat(' ').take(gap));
+    ext.extend(std::iter::repeat_n(' ', gap));
     ext.append(&mut chars);
     let max_start = ext.len().saturating_sub(width);
     let start = if max_start == 0 {
@@ -61,56 +40,289 @@ fn scrolling_window(text: &str, width: usize, offset: usize) -> String {
     ext[start..start + width].iter().collect()
 }
 
-pub fn handle_jobs() -> Result<(), Box<dyn std::error::Error>> {
-    let config = ClusterConfig::load();
-    let login = match &config.login {
-        Some(l) => l,
-        None => {
-            eprintln!("Error: No login configuration found. Run 'mct login' first.");
-            std::process::exit(1);
-        }
-    };
+// History entries shown below the queue
+const HISTORY_ROWS: usize = 10;
 
-    let username = config.get_username().ok_or_else(|| {
-        std::io::Error::new(
-            std::io::ErrorKind::Other,
-            "No username found in login or ssh config",
-        )
-    })?;
-
-    let attrs = [
-        "ClusterId",
-        "ProcId",
-        "Cmd",
-        "Args",
-        "JobStatus",
-        "RequestGPUs",
-        "JobStartDate",
-    ]
-    .join(",");
+/// Queued jobs followed by the most recent history entries, and the number of queued jobs.
+fn fetch_rows(
+    exec: &dyn RemoteExecutor,
+    username: &str,
+) -> Result<(Vec<JobAd>, usize), Box<dyn std::error::Error>> {
+    let attrs = projection(&[
+        JobAttr::Cmd,
+        JobAttr::Args,
+        JobAttr::JobStatus,
+        JobAttr::RequestGpus,
+        JobAttr::JobStartDate,
+        JobAttr::JobPrio,
+        JobAttr::Iwd,
+        JobAttr::UserLog,
+        JobAttr::Err,
+        JobAttr::Out,
+    ]);
     let cmd = format!("condor_q {} -json -attributes {}", username, attrs);
-    let out = run_remote(login, &cmd)?;
-    if !out.status.success() {
+    let out = exec.run(&cmd)?;
+    if !out.success() {
         let stderr = String::from_utf8_lossy(&out.stderr);
         return Err(format!("SSH command failed: {}", stderr).into());
     }
 
-    let running_jobs: Vec<JobRow> = parse_json_relaxed(&out.stdout)?;
-    let hist_attrs = [
-        "ClusterId",
-        "ProcId",
-        "Cmd",
-        "Args",
-        "JobStatus",
-        "RequestGPUs",
-    ]
-    .join(",");
-    let recent_hist: Vec<JobRow> = condor_history_for_user(login, &username, &hist_attrs, 10)?;
-
-    let mut rows: Vec<JobRow> = Vec::new();
+    let running_jobs: Vec<JobAd> = parse_json_relaxed(&out.stdout)?;
+    let hist_attrs = projection(&[
+        JobAttr::Cmd,
+        JobAttr::Args,
+        JobAttr::JobStatus,
+        JobAttr::RequestGpus,
+        JobAttr::Iwd,
+        JobAttr::UserLog,
+        JobAttr::Err,
+        JobAttr::Out,
+    ]);
+    let recent_hist: Vec<JobAd> =
+        condor_history_for_user(exec, username, &hist_attrs, HISTORY_ROWS, None)?;
+
+    let queued = running_jobs.len();
+    let mut rows: Vec<JobAd> = Vec::new();
     rows.extend(running_jobs);
     rows.extend(recent_hist);
+    Ok((rows, queued))
+}
+
+/// Work for the background fetcher.
+enum FetchRequest {
+    Rows,
+    /// Full ad of a job; `finished` jobs are looked up in the history only
+    Details {
+        job_id: String,
+        finished: bool,
+    },
+    /// New output of a log file shown in the log view
+    Log {
+        job_id: String,
+        path: String,
+        offset: u64,
+    },
+    /// A confirmed action, run as one remote command for the whole batch
+    Action {
+        action: JobAction,
+        job_ids: Vec<String>,
+    },
+}
+
+/// Results sent back to the UI thread.
+enum FetchEvent {
+    /// A row fetch has begun
+    Started,
+    Rows(Result<(Vec<JobAd>, usize), String>),
+    Details(String, Result<LongAd, String>),
+    Log {
+        job_id: String,
+        path: String,
+        chunk: Result<TailChunk, String>,
+    },
+    Action {
+        action: JobAction,
+        report: ActionReport,
+    },
+}
+
+/// Serve `requests` until the UI hangs up, and fetch the rows every `interval` if set.
+fn fetch_worker(
+    exec: &dyn RemoteExecutor,
+    username: &str,
+    interval: Option<Duration>,
+    requests: Receiver<FetchRequest>,
+    events: Sender<FetchEvent>,
+) {
+    let mut next_refresh = interval.and_then(|every| Instant::now().checked_add(every));
+    loop {
+        let request = match next_refresh {
+            Some(at) => match requests.recv_timeout(at.saturating_duration_since(Instant::now())) {
+                Ok(request) => request,
+                Err(RecvTimeoutError::Timeout) => FetchRequest::Rows,
+                Err(RecvTimeoutError::Disconnected) => return,
+            },
+            None => match requests.recv() {
+                Ok(request) => request,
+                Err(_) => return,
+            },
+        };
+        let event = match request {
+            FetchRequest::Rows => {
+                if events.send(FetchEvent::Started).is_err() {
+                    return;
+                }
+                let rows = fetch_rows(exec, username).map_err(|e| e.to_string());
+                next_refresh = interval.and_then(|every| Instant::now().checked_add(every));
+                FetchEvent::Rows(rows)
+            }
+            FetchRequest::Details { job_id, finished } => {
+                let ad = fetch_long_ad(exec, &job_id, finished);
+                FetchEvent::Details(job_id, ad)
+            }
+            FetchRequest::Log {
+                job_id,
+                path,
+                offset,
+            } => {
+                let chunk = fetch_tail(exec, &path, offset);
+                FetchEvent::Log {
+                    job_id,
+                    path,
+                    chunk,
+                }
+            }
+            FetchRequest::Action { action, job_ids } => {
+                let report = run_action(exec, &action, &job_ids);
+                FetchEvent::Action { action, report }
+            }
+        };
+        if events.send(event).is_err() {
+            return;
+        }
+    }
+}
+
+/// A line of the job list: a section separator or the index of a job row.
+#[derive(Debug, PartialEq)]
+enum ListLine {
+    Section(String),
+    Job(usize),
+}
+
+/// The queue section followed by the history section, each under a separator.
+fn list_lines(queued: usize, total: usize) -> Vec<ListLine> {
+    let mut lines = vec![ListLine::Section(format!("Queue ({})", queued))];
+    lines.extend((0..queued).map(ListLine::Job));
+    if total > queued {
+        lines.push(ListLine::Section(format!(
+            "History (last {})",
+            total - queued
+        )));
+        lines.extend((queued..total).map(ListLine::Job));
+    }
+    lines
+}
+
+/// `── title ─────` filling `cols` columns.
+fn separator(title: &str, cols: usize) -> String {
+    let text = format!("── {} ", title);
+    let fill = cols.saturating_sub(text.chars().count());
+    format!(
+        "{}{}",
+        text.chars().take(cols).collect::<String>(),
+        "─".repeat(fill)
+    )
+}
+
+/// Content of the detail pane: the summary, then every attribute of the ad.
+fn detail_lines(ad: &LongAd) -> Vec<String> {
+    let summary = summary_lines(ad);
+    let width = summary.iter().map(|(l, _)| l.len()).max().unwrap_or(0);
+    let mut lines: Vec<String> = summary
+        .into_iter()
+        .map(|(label, value)| format!("{:<width$}  {}", label, value, width = width))
+        .collect();
+    lines.push(String::new());
+    lines.extend(
+        ad.iter()
+            .map(|(name, value)| format!("{} = {}", name, value)),
+    );
+    lines
+}
+
+/// First visible list line, scrolled just enough to show `sel_line` (and the
+/// separator right above it) within `visible` lines.
+fn scroll_top(top: usize, sel_line: usize, lines: &[ListLine], visible: usize) -> usize {
+    if visible == 0 {
+        return sel_line;
+    }
+    // Do not leave empty space below the last line
+    let mut top = top.min(lines.len().saturating_sub(visible));
+    let first_wanted = match sel_line.checked_sub(1).map(|i| &lines[i]) {
+        Some(ListLine::Section(_)) if visible > 1 => sel_line - 1,
+        _ => sel_line,
+    };
+    if first_wanted < top {
+        top = first_wanted;
+    }
+    if sel_line >= top + visible {
+        top = sel_line + 1 - visible;
+    }
+    top
+}
+
+/// Input the TUI is waiting for on the bottom line.
+enum Prompt {
+    /// y/n before running `action` on the jobs; `skipped` jobs do not apply
+    Confirm {
+        action: JobAction,
+        job_ids: Vec<String>,
+        skipped: usize,
+    },
+    /// A new bid being typed for the jobs
+    Bid {
+        job_ids: Vec<String>,
+        skipped: usize,
+        input: String,
+    },
+}
+
+impl Prompt {
+    fn text(&self) -> String {
+        match self {
+            Prompt::Confirm {
+                action,
+                job_ids,
+                skipped,
+            } => format!(
+                "{}{}? [y/N]",
+                action.describe(&jobs_label(job_ids)),
+                skipped_note(*skipped)
+            ),
+            Prompt::Bid {
+                job_ids,
+                skipped,
+                input,
+            } => format!(
+                "New bid for {}{} (Enter to confirm, Esc to cancel): {}_",
+                jobs_label(job_ids),
+                skipped_note(*skipped),
+                input
+            ),
+        }
+    }
+}
+
+fn skipped_note(skipped: usize) -> String {
+    if skipped == 0 {
+        String::new()
+    } else {
+        format!(" ({} selected not applicable, skipped)", skipped)
+    }
+}
+
+/// Jobs an action applies to: the multi-selection if any, else the highlighted row.
+fn action_targets<'a>(
+    rows: &'a [JobAd],
+    sel: usize,
+    selected: &BTreeSet<String>,
+) -> Vec<&'a JobAd> {
+    if selected.is_empty() {
+        rows.get(sel).into_iter().collect()
+    } else {
+        rows.iter().filter(|j| selected.contains(&j.id())).collect()
+    }
+}
+
+pub fn handle_jobs(
+    exec: &dyn RemoteExecutor,
+    launcher: &Launcher,
+    refresh_every: Option<Duration>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let username = require_username(exec)?;
 
+    let (mut rows, mut queued) = fetch_rows(exec, &username)?;
     if rows.is_empty() {
         println!("No jobs found for user {}.", username);
         return Ok(());
@@ -123,18 +335,133 @@ pub fn handle_jobs() -> Result<(), Box<dyn std::error::Error>> {
     terminal::enable_raw_mode()?;
     execute!(stdout, terminal::EnterAlternateScreen, cursor::Hide)?;
 
-    let res = (|| -> Result<(), Box<dyn std::error::Error>> {
+    let res = std::thread::scope(|scope| -> Result<(), Box<dyn std::error::Error>> {
+        // Fetching happens on a worker so the UI stays responsive
+        let (requests, worker_requests) = mpsc::channel::<FetchRequest>();
+        let (worker_events, events) = mpsc::channel::<FetchEvent>();
+        let worker_username = username.clone();
+        scope.spawn(move || {
+            fetch_worker(
+                exec,
+                &worker_username,
+                refresh_every,
+                worker_requests,
+                worker_events,
+            )
+        });
+        let mut last_updated = Instant::now();
+        let mut fetching = false;
+        let mut fetch_error: Option<String> = None;
+
         // Scrolling state
         let mut scroll_offset: usize = 0;
         let mut scroll_start_at: Instant = Instant::now();
         let mut last_scroll_tick: Instant = Instant::now();
         let mut scroll_paused: bool = true;
+        // Result of the last action, shown on the bottom line
+        let mut status_line: Option<(String, Color)> = None;
+        let mut prompt: Option<Prompt> = None;
+        let mut refresh_requested = false;
+        // Job ids selected with space/'a'/'c' for batched actions
+        let mut selected: BTreeSet<String> = BTreeSet::new();
+        // First visible list line
+        let mut top: usize = 0;
+        // Detail pane (Enter) with full ads fetched on demand, per job id
+        let mut detail_open = false;
+        let mut detail_scroll: usize = 0;
+        let mut detail_cache: HashMap<String, Result<LongAd, String>> = HashMap::new();
+        let mut detail_pending: BTreeSet<String> = BTreeSet::new();
+        // Log files of a job ('l'), shown instead of the list while open
+        let mut log_view: Option<LogView> = None;
         loop {
+            if refresh_requested {
+                refresh_requested = false;
+                // Explicit refreshes (and actions) also reload cached details
+                detail_cache.clear();
+                let _ = requests.send(FetchRequest::Rows);
+            }
+
+            while let Ok(event) = events.try_recv() {
+                match event {
+                    FetchEvent::Started => fetching = true,
+                    FetchEvent::Rows(Ok((new_rows, new_queued))) => {
+                        fetching = false;
+                        fetch_error = None;
+                        last_updated = Instant::now();
+                        let changed = new_rows.len() != rows.len()
+                            || new_rows.iter().zip(&rows).any(|(a, b)| a.id() != b.id());
+                        // Keep the highlight on the same job
+                        let current = rows.get(sel).map(|j| j.id());
+                        rows = new_rows;
+                        queued = new_queued;
+                        selected.retain(|id| rows.iter().any(|j| j.id() == *id));
+                        sel = current
+                            .and_then(|id| rows.iter().position(|j| j.id() == id))
+                            .unwrap_or_else(|| sel.min(rows.len().saturating_sub(1)));
+                        if changed {
+                            scroll_offset = 0;
+                            scroll_start_at = Instant::now();
+                            last_scroll_tick = scroll_start_at;
+                        }
+                    }
+                    FetchEvent::Rows(Err(e)) => {
+                        fetching = false;
+                        fetch_error = Some(e);
+                    }
+                    FetchEvent::Details(id, ad) => {
+                        detail_pending.remove(&id);
+                        detail_cache.insert(id, ad);
+                    }
+                    FetchEvent::Log {
+                        job_id,
+                        path,
+                        chunk,
+                    } => {
+                        if let Some(view) = log_view.as_mut().filter(|v| v.job_id == job_id) {
+                            view.receive(&path, chunk);
+                        }
+                    }
+                    FetchEvent::Action { action, report } => {
+                        let color = if report.all_succeeded() {
+                            Color::Green
+                        } else if report.succeeded.is_empty() {
+                            Color::Red
+                        } else {
+                            Color::Yellow
+                        };
+                        status_line = Some((report.summary(&action), color));
+                        refresh_requested = true;
+                    }
+                }
+            }
+
+            if let Some(view) = log_view.as_mut() {
+                if let Some((path, offset)) = view.next_request(Instant::now()) {
+                    let _ = requests.send(FetchRequest::Log {
+                        job_id: view.job_id.clone(),
+                        path,
+                        offset,
+                    });
+                }
+                execute!(stdout, terminal::Clear(terminal::ClearType::All))?;
+                let (cols, height) = terminal::size()?;
+                view.draw(&mut stdout, cols as usize, height)?;
+                stdout.flush()?;
+                if crossterm::event::poll(Duration::from_millis(100))? {
+                    if let Event::Key(key) = read()? {
+                        if let LogViewKey::Close = view.handle_key(key) {
+                            log_view = None;
+                        }
+                    }
+                }
+                continue;
+            }
+
             // Render frame
             execute!(stdout, terminal::Clear(terminal::ClearType::All))?;
 
             // Terminal width for dynamic column sizing
-            let (cols, _) = terminal::size()?;
+            let (cols, height) = terminal::size()?;
             let cols = cols as usize;
             const JOBID_W: usize = 10;
             const GPUS_W: usize = 4;
@@ -162,10 +489,8 @@ pub fn handle_jobs() -> Result<(), Box<dyn std::error::Error>> {
                 cursor::MoveTo(0, row),
                 terminal::Clear(terminal::ClearType::CurrentLine)
             )?;
-            writeln!(
-                stdout,
-                "Use ↑/↓ to navigate, 'l' logs, 's' SSH, 'r' refresh, 'p' toggle scroll, 'q' quit"
-            )?;
+            let help = "↑/↓/j/k, PgUp/PgDn, Home/End navigate, Enter details, space/'a'/'c' select job/all/cluster, 'l' logs, 's' SSH, 'x' remove, 'h' hold, 'u' release, 'b' bid, 'r' refresh, 'p' scroll, 'q' quit";
+            writeln!(stdout, "{}", help.chars().take(cols).collect::<String>())?;
             row += 1;
 
             // add row explaining statuses
@@ -175,7 +500,21 @@ pub fn handle_jobs() -> Result<(), Box<dyn std::error::Error>> {
                 terminal::Clear(terminal::ClearType::CurrentLine)
             )?;
             execute!(stdout, SetForegroundColor(Color::DarkGrey))?;
-            writeln!(stdout, "I: Idle, R: Running, X: Removed, C: Completed, H: Held, O: Transferring Output, S: Suspended")?;
+            let legend = "I: Idle, R: Running, X: Removed, C: Completed, H: Held, O: Transferring Output, S: Suspended";
+            write!(stdout, "{}", legend.chars().take(cols).collect::<String>())?;
+            if !selected.is_empty() {
+                let note = format!("  [{} selected, Esc to clear]", selected.len());
+                execute!(stdout, SetForegroundColor(Color::Yellow))?;
+                write!(
+                    stdout,
+                    "{}",
+                    note.chars()
+                        .take(cols.saturating_sub(legend.chars().count()))
+                        .collect::<String>()
+                )?;
+            }
+            execute!(stdout, ResetColor)?;
+            writeln!(stdout)?;
             row += 1;
 
             // Header row
@@ -187,26 +526,67 @@ pub fn handle_jobs() -> Result<(), Box<dyn std::error::Error>> {
             let jobid_h = format!("{:>width$}", "JobID", width = JOBID_W);
             let gpus_h = format!("{:>width$}", "GPUs", width = GPUS_W);
             let cmd_h = format!("{:<width$}", "Cmd", width = cmd_w);
-            writeln!(stdout, "  S {}  {}  {}  Args", jobid_h, gpus_h, cmd_h)?;
+            write!(stdout, "  S {}  {}  {}  Args", jobid_h, gpus_h, cmd_h)?;
+            // Row counter, right-aligned
+            let counter = format!("{}/{}", (sel + 1).min(rows.len()), rows.len());
+            let header_w = 2 + 2 + JOBID_W + 2 + GPUS_W + 2 + cmd_w + 2 + "Args".len();
+            if cols > header_w + counter.len() {
+                execute!(
+                    stdout,
+                    cursor::MoveTo((cols - counter.len()) as u16, row),
+                    SetForegroundColor(Color::DarkGrey)
+                )?;
+                write!(stdout, "{}", counter)?;
+                execute!(stdout, ResetColor)?;
+            }
             row += 1;
 
-            // Rows: jobs
-            for (idx, j) in rows.iter().enumerate() {
+            // Rows: the visible window of the list, keeping the selection in view
+            let lines = list_lines(queued, rows.len());
+            let sel_line = lines
+                .iter()
+                .position(|l| *l == ListLine::Job(sel))
+                .unwrap_or(0);
+            // Header rows above, status line below; the detail pane takes the lower part
+            let available = (height as usize).saturating_sub(row as usize + 2);
+            let visible = if detail_open {
+                (available * 2 / 5).max(3).min(available)
+            } else {
+                available
+            };
+            let list_start = row;
+            let page = visible.max(1);
+            top = scroll_top(top, sel_line, &lines, visible);
+            for line in lines.iter().skip(top).take(visible) {
                 execute!(
                     stdout,
                     cursor::MoveTo(0, row),
                     terminal::Clear(terminal::ClearType::CurrentLine)
                 )?;
+                let idx = match line {
+                    ListLine::Section(title) => {
+                        execute!(stdout, SetForegroundColor(Color::DarkGrey))?;
+                        write!(stdout, "{}", separator(title, cols))?;
+                        execute!(stdout, ResetColor)?;
+                        row += 1;
+                        continue;
+                    }
+                    ListLine::Job(idx) => *idx,
+                };
+                let j = &rows[idx];
 
-                let sel_prefix = if idx == sel { "> " } else { "  " };
+                let sel_prefix = format!(
+                    "{}{}",
+                    if idx == sel { '>' } else { ' ' },
+                    if selected.contains(&j.id()) { '*' } else { ' ' }
+                );
                 let cmd_text = j.cmd.as_deref().unwrap_or("");
                 let args_text = j.args.as_deref().unwrap_or("");
                 let cmd_col = scrolling_window(cmd_text, cmd_w, scroll_offset);
 
                 // Build base without args
-                let jobid_col = format!("{:>width$}", job_id(j), width = JOBID_W);
-                let gpus_col = format!("{:>width$}", j.request_gpus, width = GPUS_W);
-                let cmd_col = cmd_col;
+                let jobid_col = format!("{:>width$}", j.id(), width = JOBID_W);
+                let gpus_col = format!("{:>width$}", j.gpus(), width = GPUS_W);
                 let base = format!("{}  {}  {}  ", jobid_col, gpus_col, cmd_col);
 
                 // Compute remaining columns for args
@@ -218,150 +598,397 @@ pub fn handle_jobs() -> Result<(), Box<dyn std::error::Error>> {
 
                 // Selection prefix and colored status
                 write!(stdout, "{}", sel_prefix)?;
-                let (status_ch, status_color) = match j.job_status {
-                    1 => ('I', Color::Blue),      // Idle
-                    2 => ('R', Color::Green),     // Running
-                    3 => ('X', Color::DarkRed),   // Removed
-                    4 => ('C', Color::DarkGreen), // Completed
-                    5 => ('H', Color::Yellow),    // Held
-                    6 => ('O', Color::Cyan),      // Transferring Output
-                    7 => ('S', Color::Magenta),   // Suspended
-                    _ => ('?', Color::White),
-                };
-                execute!(stdout, SetForegroundColor(status_color))?;
-                write!(stdout, "{} ", status_ch)?;
+                let status = j.status();
+                execute!(stdout, SetForegroundColor(status.color()))?;
+                write!(stdout, "{} ", status.letter())?;
                 execute!(stdout, ResetColor)?;
 
                 write!(stdout, "{}", base)?;
-                writeln!(stdout, "{}", args_display)?;
+                write!(stdout, "{}", args_display)?;
 
                 row += 1;
             }
+
+            if detail_open && !rows.is_empty() {
+                let job = &rows[sel];
+                let id = job.id();
+                if !detail_cache.contains_key(&id) && detail_pending.insert(id.clone()) {
+                    // History rows are not in the queue any more
+                    let _ = requests.send(FetchRequest::Details {
+                        job_id: id.clone(),
+                        finished: sel >= queued,
+                    });
+                }
+                let pane_h = available - visible;
+                let content: Vec<String> = match detail_cache.get(&id) {
+                    Some(Ok(ad)) => detail_lines(ad),
+                    Some(Err(e)) => vec![format!("Could not load details: {}", e)],
+                    None => vec![format!("Loading details for job {}...", id)],
+                };
+                detail_scroll = detail_scroll.min(content.len().saturating_sub(1));
+                let mut pane_row = list_start + visible as u16;
+                execute!(
+                    stdout,
+                    cursor::MoveTo(0, pane_row),
+                    terminal::Clear(terminal::ClearType::CurrentLine),
+                    SetForegroundColor(Color::DarkGrey)
+                )?;
+                write!(
+                    stdout,
+                    "{}",
+                    separator(&format!("Job {} (Shift+J/K scroll, Enter close)", id), cols)
+                )?;
+                execute!(stdout, ResetColor)?;
+                pane_row += 1;
+                for line in content
+                    .iter()
+                    .skip(detail_scroll)
+                    .take(pane_h.saturating_sub(1))
+                {
+                    execute!(
+                        stdout,
+                        cursor::MoveTo(0, pane_row),
+                        terminal::Clear(terminal::ClearType::CurrentLine)
+                    )?;
+                    write!(stdout, "{}", line.chars().take(cols).collect::<String>())?;
+                    pane_row += 1;
+                }
+            }
+
+            let bottom = match &prompt {
+                Some(p) => Some((p.text(), Color::Yellow)),
+                None => status_line.clone(),
+            };
+            if let Some((message, color)) = &bottom {
+                execute!(
+                    stdout,
+                    cursor::MoveTo(0, height.saturating_sub(2)),
+                    terminal::Clear(terminal::ClearType::CurrentLine),
+                    SetForegroundColor(*color)
+                )?;
+                write!(stdout, "{}", message.chars().take(cols).collect::<String>())?;
+                execute!(stdout, ResetColor)?;
+            }
+
+            // Status bar: data age, refresh state and the last fetch error
+            let mut status_bar = format!(
+                "Updated {} ago",
+                human_duration(last_updated.elapsed().as_secs())
+            );
+            match refresh_every {
+                Some(every) => status_bar.push_str(&format!(
+                    " · auto-refresh every {}",
+                    human_duration(every.as_secs())
+                )),
+                None => status_bar.push_str(" · auto-refresh off"),
+            }
+            if fetching {
+                status_bar.push_str(" · refreshing...");
+            }
+            execute!(
+                stdout,
+                cursor::MoveTo(0, height.saturating_sub(1)),
+                terminal::Clear(terminal::ClearType::CurrentLine),
+                SetForegroundColor(Color::DarkGrey)
+            )?;
+            write!(
+                stdout,
+                "{}",
+                status_bar.chars().take(cols).collect::<String>()
+            )?;
+            if let Some(e) = &fetch_error {
+                let error = format!(" · refresh failed: {}", e.replace('\n', " "));
+                execute!(stdout, SetForegroundColor(Color::Red))?;
+                write!(
+                    stdout,
+                    "{}",
+                    error
+                        .chars()
+                        .take(cols.saturating_sub(status_bar.chars().count()))
+                        .collect::<String>()
+                )?;
+            }
+            execute!(stdout, ResetColor)?;
             stdout.flush()?;
 
             // Scroll timing
             let now = Instant::now();
-            if !scroll_paused {
-                if now.duration_since(scroll_start_at) >= Duration::from_millis(150)
-                    && now.duration_since(last_scroll_tick) >= Duration::from_millis(150)
-                {
-                    scroll_offset = scroll_offset.wrapping_add(1);
-                    last_scroll_tick = now;
-                }
+            if !scroll_paused
+                && now.duration_since(scroll_start_at) >= Duration::from_millis(150)
+                && now.duration_since(last_scroll_tick) >= Duration::from_millis(150)
+            {
+                scroll_offset = scroll_offset.wrapping_add(1);
+                last_scroll_tick = now;
             }
 
             // Non-blocking input with timeout so scrolling can advance
             if crossterm::event::poll(Duration::from_millis(100))? {
-                match read()? {
+                let event = read()?;
+                if let Some(current) = prompt.take() {
+                    if let Event::Key(KeyEvent { code, .. }) = event {
+                        prompt = match (current, code) {
+                            (
+                                Prompt::Confirm {
+                                    action, job_ids, ..
+                                },
+                                KeyCode::Char('y') | KeyCode::Char('Y'),
+                            ) => {
+                                status_line = Some((
+                                    format!("{}...", action.describe(&jobs_label(&job_ids))),
+                                    Color::DarkGrey,
+                                ));
+                                selected.clear();
+                                let _ = requests.send(FetchRequest::Action { action, job_ids });
+                                None
+                            }
+                            (
+                                Prompt::Bid {
+                                    job_ids,
+                                    skipped,
+                                    mut input,
+                                },
+                                KeyCode::Char(c),
+                            ) if c.is_ascii_digit() => {
+                                input.push(c);
+                                Some(Prompt::Bid {
+                                    job_ids,
+                                    skipped,
+                                    input,
+                                })
+                            }
+                            (
+                                Prompt::Bid {
+                                    job_ids,
+                                    skipped,
+                                    mut input,
+                                },
+                                KeyCode::Backspace,
+                            ) => {
+                                input.pop();
+                                Some(Prompt::Bid {
+                                    job_ids,
+                                    skipped,
+                                    input,
+                                })
+                            }
+                            (
+                                Prompt::Bid {
+                                    job_ids,
+                                    skipped,
+                                    input,
+                                },
+                                KeyCode::Enter,
+                            ) => match parse_bid(&input) {
+                                Ok(bid) => Some(Prompt::Confirm {
+                                    action: JobAction::SetBid(bid),
+                                    job_ids,
+                                    skipped,
+                                }),
+                                Err(message) => {
+                                    status_line = Some((message, Color::Red));
+                                    None
+                                }
+                            },
+                            _ => {
+                                status_line = Some(("Cancelled".to_string(), Color::DarkGrey));
+                                None
+                            }
+                        };
+                    } else {
+                        prompt = Some(current);
+                    }
+                    continue;
+                }
+                match event {
                     Event::Key(KeyEvent {
                         code: KeyCode::Char('q'),
                         modifiers: KeyModifiers::NONE,
                         ..
                     }) => break,
                     Event::Key(KeyEvent {
-                        code: KeyCode::Up, ..
+                        code: KeyCode::Up | KeyCode::Char('k'),
+                        ..
                     }) => {
-                        if sel > 0 {
-                            sel -= 1;
-                        }
+                        sel = sel.saturating_sub(1);
                     }
                     Event::Key(KeyEvent {
-                        code: KeyCode::Down,
+                        code: KeyCode::Down | KeyCode::Char('j'),
+                        ..
+                    }) if sel + 1 < rows.len() => {
+                        sel += 1;
+                    }
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Enter,
                         ..
                     }) => {
+                        detail_open = !detail_open;
+                        detail_scroll = 0;
+                    }
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Char('J'),
+                        ..
+                    }) if detail_open => {
+                        detail_scroll += 1;
+                    }
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Char('K'),
+                        ..
+                    }) if detail_open => {
+                        detail_scroll = detail_scroll.saturating_sub(1);
+                    }
+                    Event::Key(KeyEvent {
+                        code: KeyCode::PageUp,
+                        ..
+                    }) => {
+                        sel = sel.saturating_sub(page);
+                    }
+                    Event::Key(KeyEvent {
+                        code: KeyCode::PageDown,
+                        ..
+                    }) => {
+                        sel = (sel + page).min(rows.len().saturating_sub(1));
+                    }
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Home,
+                        ..
+                    }) => {
+                        sel = 0;
+                    }
+                    Event::Key(KeyEvent {
+                        code: KeyCode::End, ..
+                    }) => {
+                        sel = rows.len().saturating_sub(1);
+                    }
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Char(' '),
+                        ..
+                    }) if !rows.is_empty() => {
+                        let id = rows[sel].id();
+                        if !selected.remove(&id) {
+                            selected.insert(id);
+                        }
                         if sel + 1 < rows.len() {
                             sel += 1;
                         }
                     }
                     Event::Key(KeyEvent {
-                        code: KeyCode::Char('l'),
+                        code: KeyCode::Char('a'),
                         modifiers: KeyModifiers::NONE,
                         ..
                     }) => {
-                        let selected = &rows[sel];
-                        execute!(stdout, terminal::LeaveAlternateScreen, cursor::Show)?;
-                        terminal::disable_raw_mode()?;
-                        let selector =
-                            Some(format!("{}.{}", selected.cluster_id, selected.proc_id));
-                        if let Err(e) = handle_logs(selector, false, false, false, None) {
-                            eprintln!("Error showing logs: {}", e);
+                        // Select every row, or clear if all already are
+                        if selected.len() == rows.len() {
+                            selected.clear();
+                        } else {
+                            selected = rows.iter().map(|j| j.id()).collect();
                         }
-                        return Ok(());
                     }
                     Event::Key(KeyEvent {
-                        code: KeyCode::Char('r'),
+                        code: KeyCode::Char('c'),
                         modifiers: KeyModifiers::NONE,
                         ..
-                    }) => {
-                        // Show immediate indicator and refresh data synchronously
-                        execute!(
-                            stdout,
-                            cursor::MoveTo(0, 1),
-                            terminal::Clear(terminal::ClearType::CurrentLine)
-                        )?;
-                        writeln!(stdout, "Refreshing...")?;
-                        stdout.flush()?;
-
-                        let cmd = format!("condor_q {} -json -attributes {}", username, attrs);
-                        match run_remote(login, &cmd) {
-                            Ok(out) => {
-                                if !out.status.success() {
-                                    let stderr = String::from_utf8_lossy(&out.stderr);
-                                    eprintln!("Refresh failed: {}", stderr);
-                                } else if let Ok(new_running) =
-                                    parse_json_relaxed::<Vec<JobRow>>(&out.stdout)
-                                {
-                                    if let Ok(new_hist) =
-                                        condor_history_for_user(login, &username, &hist_attrs, 10)
-                                    {
-                                        rows.clear();
-                                        rows.extend(new_running);
-                                        rows.extend(new_hist);
-                                        sel = if rows.is_empty() {
-                                            0
-                                        } else {
-                                            sel.min(rows.len() - 1)
-                                        };
-                                        scroll_offset = 0;
-                                        scroll_start_at = Instant::now();
-                                        last_scroll_tick = scroll_start_at;
-                                    }
-                                }
+                    }) if !rows.is_empty() => {
+                        // Toggle all procs of the highlighted job's cluster
+                        let cluster_id = rows[sel].cluster_id;
+                        let ids: Vec<String> = rows
+                            .iter()
+                            .filter(|j| j.cluster_id == cluster_id)
+                            .map(|j| j.id())
+                            .collect();
+                        if ids.iter().all(|id| selected.contains(id)) {
+                            for id in &ids {
+                                selected.remove(id);
                             }
-                            Err(e) => eprintln!("Refresh error: {}", e),
+                        } else {
+                            selected.extend(ids);
                         }
-                        // Next frame will clear the indicator
                     }
                     Event::Key(KeyEvent {
-                        code: KeyCode::Char('s'),
+                        code: KeyCode::Esc, ..
+                    }) => {
+                        selected.clear();
+                    }
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Char('l'),
+                        modifiers: KeyModifiers::NONE,
+                        ..
+                    }) if !rows.is_empty() => {
+                        // History rows are finished, so their files are read once
+                        log_view = Some(LogView::new(&rows[sel], sel >= queued));
+                    }
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Char('r'),
                         modifiers: KeyModifiers::NONE,
                         ..
                     }) => {
-                        // Open new Terminal window and SSH to job via login node
-                        let selected = &rows[sel];
-                        let jobid = format!("{}.{}", selected.cluster_id, selected.proc_id);
-                        let mut ssh_cmd = String::from("ssh ");
-                        if let Some(name) = &login.ssh_config_name {
-                            ssh_cmd.push_str(name);
-                        } else {
-                            if let Some(identity) = &login.identity_file {
-                                if !identity.is_empty() {
-                                    ssh_cmd.push_str("-i '");
-                                    ssh_cmd.push_str(&identity.replace('\'', "'\\''"));
-                                    ssh_cmd.push_str("' ");
-                                }
+                        refresh_requested = true;
+                    }
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Char(key @ ('x' | 'h' | 'u' | 'b')),
+                        modifiers: KeyModifiers::NONE,
+                        ..
+                    }) if !rows.is_empty() => {
+                        let targets = action_targets(&rows, sel, &selected);
+                        let action = match key {
+                            'x' => JobAction::Remove,
+                            'h' => JobAction::Hold,
+                            'u' => JobAction::Release,
+                            _ => JobAction::SetBid(0.0),
+                        };
+                        let (applicable, skipped): (Vec<&JobAd>, Vec<&JobAd>) = targets
+                            .iter()
+                            .partition(|j| action.unavailable_reason(j.status()).is_none());
+                        let job_ids: Vec<String> = applicable.iter().map(|j| j.id()).collect();
+                        if let [job] = targets.as_slice() {
+                            if let Some(reason) = action.unavailable_reason(job.status()) {
+                                status_line =
+                                    Some((format!("Job {} {}", job.id(), reason), Color::Red));
+                                continue;
                             }
-                            ssh_cmd.push_str(&format!("{}@{}", login.username, login.hostname));
                         }
-                        ssh_cmd.push(' ');
-                        ssh_cmd.push_str(&format!("\"condor_ssh_to_job {}\"", jobid));
-
-                        let script_cmd = ssh_cmd.replace('\\', "\\\\").replace('"', "\\\"");
-                        let osa = format!(
-                            "tell application \"Terminal\" to do script \"{}\"",
-                            script_cmd
+                        if job_ids.is_empty() {
+                            status_line = Some((
+                                format!("No selected job can be {}", action.past_participle()),
+                                Color::Red,
+                            ));
+                        } else if key == 'b' {
+                            // Start from the current bid when changing a single job
+                            let input = match applicable.as_slice() {
+                                [job] => format!("{}", job.price()),
+                                _ => String::new(),
+                            };
+                            prompt = Some(Prompt::Bid {
+                                job_ids,
+                                skipped: skipped.len(),
+                                input,
+                            });
+                        } else {
+                            prompt = Some(Prompt::Confirm {
+                                action,
+                                job_ids,
+                                skipped: skipped.len(),
+                            });
+                        }
+                    }
+                    Event::Key(KeyEvent {
+                        code: KeyCode::Char('s'),
+                        modifiers: KeyModifiers::NONE,
+                        ..
+                    }) if !rows.is_empty() => {
+                        // SSH to the job via the submit node, where the launcher decides
+                        let jobid = rows[sel].id();
+                        status_line = Some(
+                            match exec.interactive_argv(&format!("condor_ssh_to_job {}", jobid)) {
+                                None => (
+                                    "SSH is not available for this connection".to_string(),
+                                    Color::Red,
+                                ),
+                                Some(argv) => match launcher.launch(&argv, &jobid) {
+                                    Ok(message) => (message, Color::DarkGrey),
+                                    Err(message) => (message, Color::Red),
+                                },
+                            },
                         );
-                        let _ = Command::new("osascript").arg("-e").arg(osa).spawn();
                     }
                     Event::Key(KeyEvent {
                         code: KeyCode::Char('p'),
@@ -384,15 +1011,93 @@ pub fn handle_jobs() -> Result<(), Box<dyn std::error::Error>> {
             }
         }
         Ok(())
-    })();
+    });
 
     // Restore terminal
     execute!(stdout, terminal::LeaveAlternateScreen, cursor::Show)?;
     terminal::disable_raw_mode()?;
 
-    if let Err(e) = res {
-        return Err(e);
-    }
+    res?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::remote::FixtureExecutor;
+
+    #[test]
+    fn lists_queue_and_history_sections() {
+        assert_eq!(
+            list_lines(2, 3),
+            vec![
+                ListLine::Section("Queue (2)".into()),
+                ListLine::Job(0),
+                ListLine::Job(1),
+                ListLine::Section("History (last 1)".into()),
+                ListLine::Job(2),
+            ]
+        );
+        assert_eq!(list_lines(1, 1).len(), 2);
+    }
+
+    #[test]
+    fn scrolls_to_keep_selection_visible() {
+        let lines = list_lines(20, 30);
+        // Moving down past the window scrolls by one line
+        assert_eq!(scroll_top(0, 10, &lines, 10), 1);
+        // The history separator is shown with its first job when scrolling up
+        assert_eq!(scroll_top(25, 22, &lines, 10), 21);
+        // Selection already visible: no jump
+        assert_eq!(scroll_top(5, 8, &lines, 10), 5);
+        // Shrinking lists do not leave blank space below the last line
+        assert_eq!(scroll_top(28, 31, &lines, 10), 22);
+    }
+
+    #[test]
+    fn worker_refreshes_on_interval_and_runs_actions() {
+        let exec =
+            FixtureExecutor::new("barmstrong").with_stdout("condor_hold 12.0", "Job 12.0 held\n");
+        let (request_tx, request_rx) = mpsc::channel();
+        let (event_tx, event_rx) = mpsc::channel();
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                fetch_worker(
+                    &exec,
+                    "barmstrong",
+                    Some(Duration::from_millis(10)),
+                    request_rx,
+                    event_tx,
+                )
+            });
+            let timeout = Duration::from_secs(5);
+            assert!(matches!(
+                event_rx.recv_timeout(timeout),
+                Ok(FetchEvent::Started)
+            ));
+            match event_rx.recv_timeout(timeout) {
+                Ok(FetchEvent::Rows(Err(e))) => assert!(e.contains("No fixture recorded")),
+                _ => panic!("expected a failed refresh"),
+            }
+            request_tx
+                .send(FetchRequest::Action {
+                    action: JobAction::Hold,
+                    job_ids: vec!["12.0".to_string()],
+                })
+                .unwrap();
+            // Skip interval refreshes until the action's result arrives
+            loop {
+                match event_rx.recv_timeout(timeout) {
+                    Ok(FetchEvent::Action { action, report }) => {
+                        assert_eq!(report.summary(&action), "Hold job 12.0: done");
+                        break;
+                    }
+                    Ok(_) => continue,
+                    Err(_) => panic!("expected the action's result"),
+                }
+            }
+            drop(request_tx);
+        });
+    }
+}
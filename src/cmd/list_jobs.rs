@@ -1,151 +1,409 @@
-use crate::config::ClusterConfig;
-use crate::cmd::condor::condor_q_for_user;
-use crate::utils::serde::{deserialize_i64_lenient, deserialize_request_gpus};
-use comfy_table::{
-    presets::UTF8_FULL, Attribute, Cell, CellAlignment, Color, ContentArrangement, Table,
+use crate::cmd::columns::{
+    column_attrs, hold_summary, remaining_secs, runtime_secs, Column, RowContext,
 };
+use crate::cmd::condor::{
+    condor_q_for_user, price_to_job_prio, projection, JobAd, JobAttr, JobStatus,
+};
+use crate::utils::output::{OutputFormat, Records};
+use crate::utils::remote::{require_username, RemoteExecutor};
+use crate::utils::time::{human_duration, now_unix};
+use clap::ValueEnum;
+use comfy_table::{presets::UTF8_FULL, ContentArrangement, Table};
 use crossterm::terminal;
-use serde::Deserialize;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
-
-#[derive(Deserialize, Debug)]
-struct JobRow {
-    #[serde(rename = "ClusterId")]
-    cluster_id: i64,
-    #[serde(rename = "ProcId")]
-    proc_id: i64,
-    #[serde(rename = "Cmd")]
-    cmd: Option<String>,
-    #[serde(rename = "Args")]
-    args: Option<String>,
-    #[serde(rename = "JobPrio")]
-    job_prio: i32,
-    #[serde(
-        rename = "RequestGPUs",
-        default,
-        deserialize_with = "deserialize_request_gpus"
-    )]
-    request_gpus: i32,
-    #[serde(
-        rename = "JobStartDate",
-        default,
-        deserialize_with = "deserialize_i64_lenient"
-    )]
-    start_unix: i64,
-    #[allow(dead_code)]
-    #[serde(
-        rename = "QDate",
-        default,
-        deserialize_with = "deserialize_i64_lenient"
-    )]
-    q_unix: i64,
+
+/// Column to order `mct ls` rows by.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    Runtime,
+    Bid,
+    Gpus,
+    Qdate,
+    Id,
+}
+
+impl SortKey {
+    fn attrs(&self) -> &'static [JobAttr] {
+        match self {
+            SortKey::Runtime => &[JobAttr::JobStartDate],
+            SortKey::Bid => &[JobAttr::JobPrio],
+            SortKey::Gpus => &[JobAttr::RequestGpus],
+            SortKey::Qdate => &[JobAttr::QDate],
+            SortKey::Id => &[],
+        }
+    }
+}
+
+/// Job selection for `mct ls`. Everything except the `cmd` glob is also sent
+/// to `condor_q` as a constraint, so only matching ads are transferred.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct JobFilter {
+    pub statuses: Vec<JobStatus>,
+    /// `Some(true)` for GPU jobs only, `Some(false)` for CPU-only jobs
+    pub gpu: Option<bool>,
+    pub cluster: Option<i64>,
+    /// Glob matched against the executable's file name, or the full path if it contains `/`
+    pub cmd: Option<String>,
+    pub min_bid: Option<f64>,
+    pub max_bid: Option<f64>,
+}
+
+impl JobFilter {
+    /// ClassAd expression for `condor_q -constraint`, if any filter applies.
+    pub fn constraint(&self) -> Option<String> {
+        let mut clauses = Vec::new();
+        if !self.statuses.is_empty() {
+            let any = self
+                .statuses
+                .iter()
+                .map(|s| format!("JobStatus == {}", s.code()))
+                .collect::<Vec<_>>()
+                .join(" || ");
+            clauses.push(format!("({})", any));
+        }
+        match self.gpu {
+            Some(true) => clauses.push("RequestGPUs > 0".to_string()),
+            // Jobs without RequestGPUs are CPU jobs too
+            Some(false) => clauses.push("(RequestGPUs > 0) =!= true".to_string()),
+            None => {}
+        }
+        if let Some(cluster) = self.cluster {
+            clauses.push(format!("ClusterId == {}", cluster));
+        }
+        if let Some(min) = self.min_bid {
+            clauses.push(format!("JobPrio >= {}", price_to_job_prio(min)));
+        }
+        if let Some(max) = self.max_bid {
+            clauses.push(format!("JobPrio <= {}", price_to_job_prio(max)));
+        }
+        if clauses.is_empty() {
+            None
+        } else {
+            Some(clauses.join(" && "))
+        }
+    }
+
+    /// Attributes needed by [`JobFilter::matches`].
+    fn attrs(&self) -> Vec<JobAttr> {
+        let mut attrs = Vec::new();
+        if !self.statuses.is_empty() {
+            attrs.push(JobAttr::JobStatus);
+        }
+        if self.gpu.is_some() {
+            attrs.push(JobAttr::RequestGpus);
+        }
+        if self.min_bid.is_some() || self.max_bid.is_some() {
+            attrs.push(JobAttr::JobPrio);
+        }
+        if self.cmd.is_some() {
+            attrs.push(JobAttr::Cmd);
+        }
+        attrs
+    }
+
+    /// Client-side check, covering the `cmd` glob and any schedd that ignored the constraint.
+    pub fn matches(&self, job: &JobAd) -> bool {
+        if !self.statuses.is_empty() && !self.statuses.contains(&job.status()) {
+            return false;
+        }
+        if let Some(gpu) = self.gpu {
+            if (job.gpus() > 0) != gpu {
+                return false;
+            }
+        }
+        if self.cluster.is_some_and(|c| c != job.cluster_id) {
+            return false;
+        }
+        if self.min_bid.is_some_and(|min| job.price() < min)
+            || self.max_bid.is_some_and(|max| job.price() > max)
+        {
+            return false;
+        }
+        if let Some(pattern) = &self.cmd {
+            let cmd = job.cmd.as_deref().unwrap_or("");
+            let target = if pattern.contains('/') {
+                cmd
+            } else {
+                cmd.rsplit('/').next().unwrap_or(cmd)
+            };
+            if !glob_match(pattern, target) {
+                return false;
+            }
+        }
+        true
+    }
 }
 
-fn price_from_prio(job_prio: i32) -> f64 {
-    (job_prio + 1000) as f64
+/// Shell-style glob supporting `*` and `?`.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let t: Vec<char> = text.chars().collect();
+    let (mut pi, mut ti) = (0, 0);
+    // Position of the last `*` and the text index it is currently matched up to
+    let mut star: Option<(usize, usize)> = None;
+    while ti < t.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == t[ti]) {
+            pi += 1;
+            ti += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            star = Some((pi, ti));
+            pi += 1;
+        } else if let Some((sp, st)) = star {
+            pi = sp + 1;
+            ti = st + 1;
+            star = Some((sp, st + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|c| *c == '*')
 }
 
-fn human_duration_from_unix(start: i64) -> String {
-    if start <= 0 {
-        return "-".to_string();
-    }
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or(Duration::from_secs(0))
-        .as_secs() as i64;
-    let secs = (now - start).max(0) as u64;
-    let h = secs / 3600;
-    let m = (secs % 3600) / 60;
-    let s = secs % 60;
-    if h > 0 {
-        format!("{}h {:02}m", h, m)
-    } else if m > 0 {
-        format!("{}m {:02}s", m, s)
-    } else {
-        format!("{}s", s)
+fn sort_jobs(jobs: &mut [JobAd], key: SortKey, now: i64) {
+    match key {
+        SortKey::Runtime => jobs.sort_by_key(|j| runtime_secs(j, now).unwrap_or(-1)),
+        SortKey::Bid => jobs.sort_by(|a, b| a.price().total_cmp(&b.price())),
+        SortKey::Gpus => jobs.sort_by_key(|j| j.gpus()),
+        SortKey::Qdate => jobs.sort_by_key(|j| j.q_date.unwrap_or(0)),
+        SortKey::Id => jobs.sort_by_key(|j| (j.cluster_id, j.proc_id)),
     }
 }
 
-fn render_table(rows: &[JobRow]) -> Table {
+fn render_table(rows: &[JobAd], columns: &[Column], ctx: &RowContext) -> Table {
     let mut table = Table::new();
     table
         .load_preset(UTF8_FULL)
         .set_content_arrangement(ContentArrangement::Dynamic)
-        .set_header(vec![
-            Cell::new("JobID").add_attribute(Attribute::Bold),
-            Cell::new("Cmd").add_attribute(Attribute::Bold),
-            Cell::new("Args").add_attribute(Attribute::Bold),
-            Cell::new("Runtime").add_attribute(Attribute::Bold),
-            Cell::new("GPUs")
-                .add_attribute(Attribute::Bold)
-                .set_alignment(CellAlignment::Right),
-            Cell::new("Bid")
-                .add_attribute(Attribute::Bold)
-                .set_alignment(CellAlignment::Right),
-        ]);
+        .set_header(columns.iter().map(|c| c.header_cell()).collect::<Vec<_>>());
 
-    let (cols, _) = terminal::size().unwrap();
-    table.set_width(cols as u16);
+    if let Ok((cols, _)) = terminal::size() {
+        table.set_width(cols);
+    }
 
     for j in rows {
-        let jobid = format!("{}.{}", j.cluster_id, j.proc_id);
-        let runtime = human_duration_from_unix(j.start_unix);
-        let gpus = j.request_gpus;
-        let bid = price_from_prio(j.job_prio);
-
-        table.add_row(vec![
-            Cell::new(jobid).fg(Color::Green),
-            Cell::new(j.cmd.as_deref().unwrap_or("")),
-            Cell::new(j.args.as_deref().unwrap_or("")),
-            Cell::new(runtime),
-            Cell::new(gpus.to_string()).set_alignment(CellAlignment::Right),
-            Cell::new(format!("{:.0}", bid)).set_alignment(CellAlignment::Right),
-        ]);
+        table.add_row(columns.iter().map(|c| c.cell(j, ctx)).collect::<Vec<_>>());
     }
 
     table
 }
 
-pub fn handle_list_jobs() -> Result<(), Box<dyn std::error::Error>> {
-    let config = ClusterConfig::load();
-    let login = match &config.login {
-        Some(l) => l,
-        None => {
-            eprintln!("Error: No login configuration found. Run 'mct login' first.");
-            std::process::exit(1);
-        }
-    };
+fn job_records(rows: &[JobAd], columns: &[Column], ctx: &RowContext) -> Records {
+    let mut records = Records::new(
+        columns
+            .iter()
+            .flat_map(|c| c.fields().iter().copied())
+            .collect(),
+    );
+    for j in rows {
+        records.push(columns.iter().flat_map(|c| c.values(j, ctx)).collect());
+    }
+    records
+}
+
+/// Query `username`'s jobs with the attributes the columns, filters and sort key
+/// need, then apply the client-side filters and the sort order.
+fn fetch_listed_jobs(
+    exec: &dyn RemoteExecutor,
+    username: &str,
+    columns: &[Column],
+    filter: &JobFilter,
+    sort: Option<SortKey>,
+    reverse: bool,
+    now: i64,
+) -> Result<Vec<JobAd>, Box<dyn std::error::Error>> {
+    let mut wanted = column_attrs(columns);
+    wanted.extend(filter.attrs());
+    if let Some(key) = sort {
+        wanted.extend_from_slice(key.attrs());
+    }
+    let attrs = projection(&wanted);
+    let constraint = filter.constraint();
+    let mut jobs: Vec<JobAd> = condor_q_for_user(exec, username, &attrs, constraint.as_deref())?;
+    jobs.retain(|j| filter.matches(j));
+    if let Some(key) = sort {
+        sort_jobs(&mut jobs, key, now);
+    }
+    if reverse {
+        jobs.reverse();
+    }
+    Ok(jobs)
+}
+
+pub fn handle_list_jobs(
+    exec: &dyn RemoteExecutor,
+    columns: &[Column],
+    warn_within: u64,
+    filter: &JobFilter,
+    sort: Option<SortKey>,
+    reverse: bool,
+    output: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let username = require_username(exec)?;
+    let now = now_unix();
+    let jobs = fetch_listed_jobs(exec, &username, columns, filter, sort, reverse, now)?;
+    let ctx = RowContext { now, warn_within };
+    if !output.is_table() {
+        print!("{}", job_records(&jobs, columns, &ctx).render(output));
+        return Ok(());
+    }
 
-    let username = config.get_username().ok_or_else(|| {
-        std::io::Error::new(
-            std::io::ErrorKind::Other,
-            "No username found in login or ssh config",
-        )
-    })?;
-
-    // Query current user's jobs (all states) with needed attributes
-    let attrs = [
-        "ClusterId",
-        "ProcId",
-        "Cmd",
-        "Args",
-        "JobPrio",
-        "RequestGPUs",
-        "RequestMemory",
-        "MemoryProvisioned",
-        "RequestCpus",
-        "CpusProvisioned",
-        "JobStartDate",
-        "QDate",
-    ]
-    .join(",");
-    let jobs: Vec<JobRow> = condor_q_for_user(login, &username, &attrs)?;
     if jobs.is_empty() {
-        println!("No jobs found for user {}.", username);
+        if *filter == JobFilter::default() {
+            println!("No jobs found for user {}.", username);
+        } else {
+            println!("No jobs matching the filters for user {}.", username);
+        }
         return Ok(());
     }
 
-    let table = render_table(&jobs);
+    let table = render_table(&jobs, columns, &ctx);
     println!("{}", table);
+
+    // Without the Hold Reason column, give a one-line hint per held job
+    if columns.contains(&Column::Status) && !columns.contains(&Column::HoldReason) {
+        let width = terminal::size().map(|(c, _)| c as usize).unwrap_or(120);
+        for j in &jobs {
+            if let Some(reason) = hold_summary(j) {
+                let line = format!("Held: job {} {}", j.id(), reason);
+                if line.chars().count() > width {
+                    let cut: String = line.chars().take(width.saturating_sub(3)).collect();
+                    println!("{}...", cut);
+                } else {
+                    println!("{}", line);
+                }
+            }
+        }
+    }
+
+    // Runtime limits are only known when the Remaining column fetched them
+    if columns.contains(&Column::Remaining) {
+        for j in &jobs {
+            if let Some(secs) = remaining_secs(j, now) {
+                if secs as u64 <= warn_within {
+                    println!(
+                        "Warning: job {} will be removed for exceeding its runtime limit in {}",
+                        j.id(),
+                        human_duration(secs as u64)
+                    );
+                }
+            }
+        }
+    }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::columns::DEFAULT_COLUMNS;
+    use crate::utils::remote::FixtureExecutor;
+
+    #[test]
+    fn lists_captured_jobs() {
+        let exec = FixtureExecutor::new("barmstrong").with_stdout(
+            "condor_q barmstrong -json -attributes ClusterId,ProcId,JobStatus,HoldReason,HoldReasonCode,Cmd,Args,JobStartDate,JobCurrentStartDate,EnteredCurrentStatus,RemoteWallClockTime,PeriodicRemove,MaxJobRetirementTime,RequestGPUs,JobPrio",
+            include_str!("../../example.json"),
+        );
+        let ctx = RowContext {
+            now: 1754666000,
+            warn_within: 1800,
+        };
+        let jobs = fetch_listed_jobs(
+            &exec,
+            "barmstrong",
+            DEFAULT_COLUMNS,
+            &JobFilter::default(),
+            None,
+            false,
+            ctx.now,
+        )
+        .unwrap();
+        let json: serde_json::Value = serde_json::from_str(
+            &job_records(&jobs, DEFAULT_COLUMNS, &ctx).render(OutputFormat::Json),
+        )
+        .unwrap();
+        assert_eq!(json[0]["job_id"], "16337546.0");
+        assert_eq!(json[0]["runtime_secs"], 583);
+    }
+
+    #[test]
+    fn filters_and_sorts_listed_jobs() {
+        let queue = serde_json::json!([
+            {"ClusterId": 1, "ProcId": 0, "Cmd": "/runs/train_a.sh", "JobPrio": -800, "RequestGPUs": 1},
+            {"ClusterId": 1, "ProcId": 1, "Cmd": "/runs/eval.sh", "JobPrio": -500, "RequestGPUs": 1},
+            {"ClusterId": 2, "ProcId": 0, "Cmd": "/runs/train_b.sh", "JobPrio": -600, "RequestGPUs": 2}
+        ]);
+        let exec = FixtureExecutor::new("barmstrong").with_stdout(
+            "condor_q barmstrong -json -attributes ClusterId,ProcId,JobPrio,RequestGPUs,Cmd -constraint 'RequestGPUs > 0'",
+            queue.to_string(),
+        );
+        let filter = JobFilter {
+            gpu: Some(true),
+            cmd: Some("train*".into()),
+            ..Default::default()
+        };
+        let columns = [Column::Id, Column::Bid];
+        let jobs = fetch_listed_jobs(
+            &exec,
+            "barmstrong",
+            &columns,
+            &filter,
+            Some(SortKey::Bid),
+            true,
+            0,
+        )
+        .unwrap();
+        let ctx = RowContext {
+            now: 0,
+            warn_within: 1800,
+        };
+        assert_eq!(
+            job_records(&jobs, &columns, &ctx).render(OutputFormat::Csv),
+            "job_id,cluster_id,proc_id,bid\n2.0,2,0,400.0\n1.0,1,0,200.0\n"
+        );
+    }
+
+    #[test]
+    fn filter_builds_constraint() {
+        let filter = JobFilter {
+            statuses: vec![JobStatus::Idle, JobStatus::Held],
+            gpu: Some(true),
+            cluster: Some(42),
+            cmd: Some("train*.sh".into()),
+            min_bid: Some(250.0),
+            max_bid: None,
+        };
+        assert_eq!(
+            filter.constraint().as_deref(),
+            Some("(JobStatus == 1 || JobStatus == 5) && RequestGPUs > 0 && ClusterId == 42 && JobPrio >= -750")
+        );
+        assert_eq!(JobFilter::default().constraint(), None);
+    }
+
+    #[test]
+    fn filter_matches_cmd_glob_on_file_name() {
+        let jobs: Vec<JobAd> = serde_json::from_str(include_str!("../../example.json")).unwrap();
+        let job = &jobs[0];
+        let with_cmd = |cmd: &str| JobFilter {
+            cmd: Some(cmd.into()),
+            ..Default::default()
+        };
+        assert!(with_cmd("cuda_*.sh").matches(job));
+        assert!(with_cmd("*/htcondor/*").matches(job));
+        assert!(!with_cmd("train*").matches(job));
+        assert!(!JobFilter {
+            gpu: Some(false),
+            ..Default::default()
+        }
+        .matches(job));
+    }
+
+    #[test]
+    fn glob_supports_star_and_question_mark() {
+        assert!(glob_match("*", ""));
+        assert!(glob_match("a*c", "abbbc"));
+        assert!(glob_match("a?c", "abc"));
+        assert!(!glob_match("a?c", "abbc"));
+        assert!(glob_match("*.py", "x.tar.py"));
+        assert!(!glob_match("*.py", "x.pyc"));
+    }
+}
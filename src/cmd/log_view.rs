@@ -0,0 +1,600 @@
+//! Scrollable, live-tailing view of a job's Out/Err/Log files inside `mct jobs`.
+
+use crate::cmd::condor::JobAd;
+use crate::cmd::logs::{job_files, LogFiles};
+use crate::utils::remote::RemoteExecutor;
+use crate::utils::ssh::shell_escape_single_quotes;
+use crossterm::event::{KeyCode, KeyEvent};
+use crossterm::{
+    cursor, execute,
+    style::{Attribute, Color, ResetColor, SetAttribute, SetBackgroundColor, SetForegroundColor},
+    terminal,
+};
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+// Most bytes fetched per request: the tail of the file when it is opened, and
+// at most this much of what was appended since the last poll
+const TAIL_BYTES: u64 = 256 * 1024;
+// Lines kept per file; older ones are dropped as new output arrives
+const MAX_LINES: usize = 20_000;
+// How often the open file of a queued job is polled for new output
+pub const LOG_POLL: Duration = Duration::from_secs(2);
+
+/// Bytes of a file starting at `start`, of a file that was `size` bytes long.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TailChunk {
+    pub size: u64,
+    pub start: u64,
+    pub data: Vec<u8>,
+}
+
+/// Remote command printing `<size> <start>` and then the file from byte `start`:
+/// everything after `offset`, restarting at 0 if the file shrank (truncation)
+/// and skipping ahead if more than [`TAIL_BYTES`] are new.
+fn tail_command(path: &str, offset: u64) -> String {
+    format!(
+        "f='{path}'; s=$(wc -c < \"$f\") || exit 1; o={offset}; \
+         if [ \"$s\" -lt \"$o\" ]; then o=0; fi; \
+         if [ $((s - o)) -gt {max} ]; then o=$((s - {max})); fi; \
+         echo \"$s $o\"; tail -c +$((o + 1)) \"$f\" | head -c $((s - o))",
+        path = shell_escape_single_quotes(path),
+        offset = offset,
+        max = TAIL_BYTES,
+    )
+}
+
+fn parse_tail_output(stdout: &[u8]) -> Result<TailChunk, String> {
+    let split = stdout
+        .iter()
+        .position(|b| *b == b'\n')
+        .ok_or("unexpected output from tail")?;
+    let header = String::from_utf8_lossy(&stdout[..split]);
+    let mut numbers = header.split_whitespace().map(|n| n.parse::<u64>());
+    match (numbers.next(), numbers.next()) {
+        (Some(Ok(size)), Some(Ok(start))) => Ok(TailChunk {
+            size,
+            start,
+            data: stdout[split + 1..].to_vec(),
+        }),
+        _ => Err(format!("unexpected output from tail: {}", header.trim())),
+    }
+}
+
+/// Read `path` from byte `offset` on, as described in [`tail_command`].
+pub fn fetch_tail(exec: &dyn RemoteExecutor, path: &str, offset: u64) -> Result<TailChunk, String> {
+    let out = exec
+        .run(&tail_command(path, offset))
+        .map_err(|e| e.to_string())?;
+    if !out.success() {
+        let stderr = String::from_utf8_lossy(&out.stderr);
+        let message = stderr.trim();
+        return Err(if message.is_empty() {
+            format!("could not read {}", path)
+        } else {
+            message.to_string()
+        });
+    }
+    parse_tail_output(&out.stdout)
+}
+
+// Show what a terminal would: the text after the last carriage return
+// (progress bars) with tabs expanded and other control characters dropped
+fn display_text(raw: &str) -> String {
+    let text = raw.rsplit('\r').next().unwrap_or("");
+    let mut shown = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\t' => shown.push_str("    "),
+            c if c.is_control() => {}
+            c => shown.push(c),
+        }
+    }
+    shown
+}
+
+/// One file of the job and the lines loaded from it so far.
+struct LogTab {
+    label: String,
+    path: String,
+    lines: Vec<String>,
+    // The last line has no newline yet and continues with the next chunk
+    open_line: bool,
+    // Raw bytes of the current last line, so progress bars collapse correctly
+    raw_last: String,
+    offset: u64,
+    loaded: bool,
+    error: Option<String>,
+    pending: bool,
+    last_poll: Option<Instant>,
+}
+
+impl LogTab {
+    fn new(label: String, path: String) -> Self {
+        Self {
+            label,
+            path,
+            lines: Vec::new(),
+            open_line: false,
+            raw_last: String::new(),
+            offset: 0,
+            loaded: false,
+            error: None,
+            pending: false,
+            last_poll: None,
+        }
+    }
+
+    /// Append a chunk; returns how many lines were dropped from the front.
+    fn apply(&mut self, chunk: TailChunk) -> usize {
+        let mut dropped = 0;
+        let mut text = String::from_utf8_lossy(&chunk.data).into_owned();
+        if chunk.start != self.offset || !self.loaded {
+            // Truncated, skipped ahead or first load: start over
+            dropped = self.lines.len();
+            self.lines.clear();
+            self.open_line = false;
+            self.raw_last.clear();
+            if chunk.start > 0 {
+                // The first line is cut off
+                text = match text.split_once('\n') {
+                    Some((_, rest)) => rest.to_string(),
+                    None => String::new(),
+                };
+            }
+        }
+        self.offset = chunk.start + chunk.data.len() as u64;
+        self.loaded = true;
+        self.error = None;
+
+        let mut parts = text.split('\n').peekable();
+        if self.open_line {
+            if let Some(first) = parts.next() {
+                self.raw_last.push_str(first);
+                if let Some(last) = self.lines.last_mut() {
+                    *last = display_text(&self.raw_last);
+                }
+                self.open_line = parts.peek().is_none();
+            }
+        }
+        while let Some(part) = parts.next() {
+            if parts.peek().is_none() {
+                // Text after the final newline (empty if the chunk ended with one)
+                if !part.is_empty() {
+                    self.raw_last = part.to_string();
+                    self.lines.push(display_text(part));
+                    self.open_line = true;
+                }
+                break;
+            }
+            self.lines.push(display_text(part));
+        }
+        if self.lines.len() > MAX_LINES {
+            let excess = self.lines.len() - MAX_LINES;
+            self.lines.drain(..excess);
+            dropped += excess;
+        }
+        dropped
+    }
+}
+
+/// Byte range of the first case-insensitive match of `query` in `line`.
+fn find_in_line(line: &str, query: &str) -> Option<(usize, usize)> {
+    if query.is_empty() {
+        return None;
+    }
+    let start = line
+        .to_ascii_lowercase()
+        .find(&query.to_ascii_lowercase())?;
+    Some((start, start + query.len()))
+}
+
+/// Index of the first line matching `query` from `from` on, or going up, the last one before `from`.
+fn find_match(lines: &[String], query: &str, from: usize, down: bool) -> Option<usize> {
+    let matches = |i: &usize| find_in_line(&lines[*i], query).is_some();
+    if down {
+        (from..lines.len()).find(matches)
+    } else {
+        (0..from.min(lines.len())).rev().find(matches)
+    }
+}
+
+/// What the jobs TUI should do after a key press in the log view.
+pub enum LogViewKey {
+    Handled,
+    Close,
+}
+
+/// Full-screen log view of one job, opened with `l` from the job list.
+pub struct LogView {
+    pub job_id: String,
+    // Finished jobs do not write any more output
+    finished: bool,
+    tabs: Vec<LogTab>,
+    active: usize,
+    // First visible line of the active file
+    top: usize,
+    // Keep the end of the file in view as output arrives
+    follow: bool,
+    search: Option<String>,
+    // Line of the match jumped to last, where n/N continue from
+    current_match: Option<usize>,
+    // Search being typed after `/`
+    input: Option<String>,
+    message: Option<String>,
+    page: usize,
+}
+
+impl LogView {
+    pub fn new(job: &JobAd, finished: bool) -> Self {
+        let mut tabs: Vec<LogTab> = job_files(job, LogFiles::default())
+            .into_iter()
+            .map(|(label, path)| LogTab::new(label, path))
+            .collect();
+        // Job output first; the user log is mostly of interest for scheduling events
+        tabs.sort_by_key(|t| t.label == "Log");
+        LogView {
+            job_id: job.id(),
+            finished,
+            tabs,
+            active: 0,
+            top: 0,
+            follow: true,
+            search: None,
+            current_match: None,
+            input: None,
+            message: None,
+            page: 10,
+        }
+    }
+
+    /// Path and offset to fetch next: the active file when it has not been
+    /// loaded yet, or when a running job's file is due for another poll.
+    pub fn next_request(&mut self, now: Instant) -> Option<(String, u64)> {
+        let finished = self.finished;
+        let tab = self.tabs.get_mut(self.active)?;
+        if tab.pending {
+            return None;
+        }
+        let due = match tab.last_poll {
+            None => true,
+            // Keep polling after errors: idle jobs have not created their files yet
+            Some(at) => !finished && now.duration_since(at) >= LOG_POLL,
+        };
+        if !due {
+            return None;
+        }
+        tab.pending = true;
+        tab.last_poll = Some(now);
+        Some((tab.path.clone(), tab.offset))
+    }
+
+    /// Take in the result of a request made with [`LogView::next_request`].
+    pub fn receive(&mut self, path: &str, result: Result<TailChunk, String>) {
+        let active = self.active;
+        let Some((index, tab)) = self
+            .tabs
+            .iter_mut()
+            .enumerate()
+            .find(|(_, t)| t.path == path && t.pending)
+        else {
+            return;
+        };
+        tab.pending = false;
+        match result {
+            Ok(chunk) => {
+                let dropped = tab.apply(chunk);
+                if index == active {
+                    self.top = self.top.saturating_sub(dropped);
+                }
+            }
+            Err(e) => tab.error = Some(e),
+        }
+    }
+
+    fn switch_tab(&mut self, index: usize) {
+        if index < self.tabs.len() && index != self.active {
+            self.active = index;
+            self.top = 0;
+            self.follow = true;
+            self.current_match = None;
+            self.message = None;
+        }
+    }
+
+    fn line_count(&self) -> usize {
+        self.tabs.get(self.active).map_or(0, |t| t.lines.len())
+    }
+
+    fn scroll_by(&mut self, delta: isize) {
+        let max_top = self.line_count().saturating_sub(self.page);
+        self.top = self.top.saturating_add_signed(delta).min(max_top);
+        self.follow = self.top >= max_top;
+    }
+
+    fn jump_to_match(&mut self, down: bool) {
+        let Some(query) = self.search.clone() else {
+            self.message = Some("No search; press / to search".to_string());
+            return;
+        };
+        let Some(tab) = self.tabs.get(self.active) else {
+            return;
+        };
+        // Continue from the last match if it is still in view, else from the view's edge
+        let bottom = (self.top + self.page).min(tab.lines.len());
+        let from = match self
+            .current_match
+            .filter(|m| (self.top..bottom).contains(m))
+        {
+            Some(m) if down => m + 1,
+            Some(m) => m,
+            None if down => self.top,
+            None => bottom,
+        };
+        match find_match(&tab.lines, &query, from, down) {
+            Some(line) => {
+                self.current_match = Some(line);
+                self.top = line.saturating_sub(self.page / 2);
+                self.follow = false;
+                self.message = None;
+            }
+            None => {
+                self.message = Some(format!(
+                    "'{}' not found {}",
+                    query,
+                    if down { "below" } else { "above" }
+                ))
+            }
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> LogViewKey {
+        if let Some(mut input) = self.input.take() {
+            match key.code {
+                KeyCode::Enter => {
+                    if input.is_empty() {
+                        self.search = None;
+                    } else {
+                        self.search = Some(input);
+                        self.current_match = None;
+                        // Most recent match first
+                        self.jump_to_match(false);
+                    }
+                }
+                KeyCode::Esc => {}
+                KeyCode::Backspace => {
+                    input.pop();
+                    self.input = Some(input);
+                }
+                KeyCode::Char(c) => {
+                    input.push(c);
+                    self.input = Some(input);
+                }
+                _ => self.input = Some(input),
+            }
+            return LogViewKey::Handled;
+        }
+        self.message = None;
+        let page = self.page as isize;
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => return LogViewKey::Close,
+            KeyCode::Tab | KeyCode::Right => {
+                self.switch_tab((self.active + 1) % self.tabs.len().max(1))
+            }
+            KeyCode::BackTab | KeyCode::Left => {
+                let count = self.tabs.len().max(1);
+                self.switch_tab((self.active + count - 1) % count)
+            }
+            KeyCode::Char(c @ '1'..='9') => self.switch_tab(c as usize - '1' as usize),
+            KeyCode::Up | KeyCode::Char('k') => self.scroll_by(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.scroll_by(1),
+            KeyCode::PageUp => self.scroll_by(-page),
+            KeyCode::PageDown | KeyCode::Char(' ') => self.scroll_by(page),
+            KeyCode::Home | KeyCode::Char('g') => {
+                self.top = 0;
+                self.follow = false;
+            }
+            KeyCode::End | KeyCode::Char('G') => self.follow = true,
+            KeyCode::Char('f') => self.follow = !self.follow,
+            KeyCode::Char('/') => self.input = Some(String::new()),
+            KeyCode::Char('n') => self.jump_to_match(true),
+            KeyCode::Char('N') => self.jump_to_match(false),
+            _ => {}
+        }
+        LogViewKey::Handled
+    }
+
+    pub fn draw(
+        &mut self,
+        stdout: &mut impl Write,
+        cols: usize,
+        height: u16,
+    ) -> std::io::Result<()> {
+        let clip = |s: &str, width: usize| s.chars().take(width).collect::<String>();
+        self.page = (height as usize).saturating_sub(4).max(1);
+
+        // Row 0: tabs
+        execute!(stdout, cursor::MoveTo(0, 0))?;
+        let title = format!("Logs of job {}  ", self.job_id);
+        write!(stdout, "{}", clip(&title, cols))?;
+        let mut used = title.chars().count();
+        for (i, tab) in self.tabs.iter().enumerate() {
+            let label = format!(" {} {} ", i + 1, tab.label);
+            if used + label.len() + 1 > cols {
+                break;
+            }
+            if i == self.active {
+                execute!(stdout, SetAttribute(Attribute::Reverse))?;
+            }
+            write!(stdout, "{}", label)?;
+            execute!(stdout, SetAttribute(Attribute::Reset))?;
+            write!(stdout, " ")?;
+            used += label.len() + 1;
+        }
+
+        let Some(tab) = self.tabs.get(self.active) else {
+            execute!(stdout, cursor::MoveTo(0, 2))?;
+            write!(
+                stdout,
+                "{}",
+                clip(&format!("No log files set for job {}", self.job_id), cols)
+            )?;
+            return self.draw_help(stdout, cols, height);
+        };
+
+        // Row 1: path and position
+        let max_top = tab.lines.len().saturating_sub(self.page);
+        if self.follow {
+            self.top = max_top;
+        }
+        self.top = self.top.min(max_top);
+        let position = if tab.lines.is_empty() {
+            String::new()
+        } else {
+            format!(
+                "  lines {}-{} of {}",
+                self.top + 1,
+                (self.top + self.page).min(tab.lines.len()),
+                tab.lines.len()
+            )
+        };
+        let state = if self.finished {
+            ""
+        } else if self.follow {
+            "  [following]"
+        } else {
+            "  [paused]"
+        };
+        execute!(
+            stdout,
+            cursor::MoveTo(0, 1),
+            SetForegroundColor(Color::DarkGrey)
+        )?;
+        write!(
+            stdout,
+            "{}",
+            clip(&format!("{}{}{}", tab.path, position, state), cols)
+        )?;
+        execute!(stdout, ResetColor)?;
+
+        // Body
+        let body: Vec<String> = match (&tab.error, tab.loaded) {
+            (Some(e), _) => vec![format!("Could not read {}: {}", tab.path, e)],
+            (None, false) => vec![format!("Loading {}...", tab.path)],
+            (None, true) if tab.lines.is_empty() => vec!["(empty)".to_string()],
+            _ => Vec::new(),
+        };
+        let mut row: u16 = 2;
+        for line in &body {
+            execute!(stdout, cursor::MoveTo(0, row))?;
+            write!(stdout, "{}", clip(line, cols))?;
+            row += 1;
+        }
+        for line in tab.lines.iter().skip(self.top).take(self.page) {
+            execute!(stdout, cursor::MoveTo(0, row))?;
+            let shown = clip(line, cols);
+            match self.search.as_deref().and_then(|q| find_in_line(&shown, q)) {
+                Some((start, end)) => {
+                    write!(stdout, "{}", &shown[..start])?;
+                    execute!(
+                        stdout,
+                        SetBackgroundColor(Color::Yellow),
+                        SetForegroundColor(Color::Black)
+                    )?;
+                    write!(stdout, "{}", &shown[start..end])?;
+                    execute!(stdout, ResetColor)?;
+                    write!(stdout, "{}", &shown[end..])?;
+                }
+                None => write!(stdout, "{}", shown)?,
+            }
+            row += 1;
+        }
+        self.draw_help(stdout, cols, height)
+    }
+
+    fn draw_help(&self, stdout: &mut impl Write, cols: usize, height: u16) -> std::io::Result<()> {
+        let prompt = match (&self.input, &self.message) {
+            (Some(input), _) => Some((format!("/{}", input), Color::Yellow)),
+            (None, Some(message)) => Some((message.clone(), Color::Yellow)),
+            (None, None) => self.search.as_ref().map(|q| {
+                (
+                    format!("Search: {} (n/N next/previous)", q),
+                    Color::DarkGrey,
+                )
+            }),
+        };
+        if let Some((text, color)) = prompt {
+            execute!(
+                stdout,
+                cursor::MoveTo(0, height.saturating_sub(2)),
+                terminal::Clear(terminal::ClearType::CurrentLine),
+                SetForegroundColor(color)
+            )?;
+            write!(stdout, "{}", text.chars().take(cols).collect::<String>())?;
+            execute!(stdout, ResetColor)?;
+        }
+        let help = "Tab/←/→/1-3 file, ↑/↓/j/k, PgUp/PgDn, Home/End scroll, 'f' follow, '/' search, 'n'/'N' next/previous, Esc back";
+        execute!(
+            stdout,
+            cursor::MoveTo(0, height.saturating_sub(1)),
+            terminal::Clear(terminal::ClearType::CurrentLine),
+            SetForegroundColor(Color::DarkGrey)
+        )?;
+        write!(stdout, "{}", help.chars().take(cols).collect::<String>())?;
+        execute!(stdout, ResetColor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::remote::FixtureExecutor;
+
+    fn chunk(start: u64, data: &str) -> TailChunk {
+        TailChunk {
+            size: start + data.len() as u64,
+            start,
+            data: data.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn parses_remote_tail_output() {
+        let exec = FixtureExecutor::new("barmstrong")
+            .with_stdout(&tail_command("/runs/it's/out.txt", 0), "11 0\nepoch 1\nepo");
+        assert_eq!(
+            fetch_tail(&exec, "/runs/it's/out.txt", 0),
+            Ok(chunk(0, "epoch 1\nepo"))
+        );
+        assert!(exec.calls()[0].starts_with("f='/runs/it'\\''s/out.txt'; "));
+    }
+
+    #[test]
+    fn appends_chunks_and_resets_after_truncation() {
+        let mut tab = LogTab::new("Out".into(), "/o".into());
+        // Opened mid-file: the cut-off first line is dropped
+        tab.apply(chunk(100, "ial\nstep 1\nstep 2: 10%\rstep 2: 20%"));
+        assert_eq!(tab.lines, vec!["step 1", "step 2: 20%"]);
+        tab.apply(chunk(tab.offset, "\rstep 2: 30%\nstep\t3\n"));
+        assert_eq!(tab.lines, vec!["step 1", "step 2: 30%", "step    3"]);
+        assert!(!tab.open_line);
+        // The file shrank, so the tail restarts from the beginning
+        let dropped = tab.apply(chunk(0, "restarted\n"));
+        assert_eq!(dropped, 3);
+        assert_eq!(tab.lines, vec!["restarted"]);
+    }
+
+    #[test]
+    fn searches_up_and_down_case_insensitively() {
+        let lines: Vec<String> = ["ok", "CUDA error", "ok", "cuda error again"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(find_match(&lines, "cuda", 4, false), Some(3));
+        assert_eq!(find_match(&lines, "cuda", 3, false), Some(1));
+        assert_eq!(find_match(&lines, "cuda", 1, true), Some(1));
+        assert_eq!(find_match(&lines, "cuda", 2, true), Some(3));
+        assert_eq!(find_match(&lines, "nan", 0, true), None);
+        assert_eq!(find_in_line("loss NaN", "nan"), Some((5, 8)));
+    }
+}
@@ -1,8 +1,19 @@
-use crate::config::{ClusterConfig, LoginConfig};
+use crate::config::{ClusterConfig, LoginConfig, DEFAULT_PROFILE_NAME};
 use dialoguer::{Input, Select};
 
-pub fn handle_login() -> Result<(), Box<dyn std::error::Error>> {
-    println!("Configure cluster login credentials");
+pub fn handle_login(
+    profile: Option<&str>,
+    set_default: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut config = ClusterConfig::load();
+    let profile_name = config
+        .profile_name(profile)
+        .unwrap_or_else(|| DEFAULT_PROFILE_NAME.to_string());
+
+    println!(
+        "Configure cluster login credentials for profile '{}'",
+        profile_name
+    );
 
     let options = vec![
         "Use SSH config entry (recommended)",
@@ -28,6 +39,8 @@ pub fn handle_login() -> Result<(), Box<dyn std::error::Error>> {
                 username: String::new(), // Will be resolved from SSH config
                 identity_file: None,
                 ssh_config_name: Some(ssh_config_name),
+                multiplex: true,
+                control_persist: None,
             }
         }
         1 => {
@@ -50,13 +63,18 @@ pub fn handle_login() -> Result<(), Box<dyn std::error::Error>> {
                     Some(identity_file)
                 },
                 ssh_config_name: None,
+                multiplex: true,
+                control_persist: None,
             }
         }
         _ => unreachable!(),
     };
 
-    let mut config = ClusterConfig::load();
-    config.login = Some(login_config);
+    config.set_login(&profile_name, login_config);
+    // The first profile configured becomes the default
+    if set_default || config.default_profile.is_none() {
+        config.default_profile = Some(profile_name);
+    }
     config.save()?;
 
     Ok(())
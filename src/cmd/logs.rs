@@ -1,136 +1,308 @@
-use crate::config::ClusterConfig;
-use crate::utils::ssh::{build_path, parse_json_relaxed, run_remote, shell_escape_single_quotes};
-use serde::Deserialize;
-
-#[derive(Deserialize, Debug)]
-struct JobInfo {
-    #[serde(rename = "ClusterId")]
-    cluster_id: i32,
-    #[serde(rename = "ProcId")]
-    proc_id: i32,
-
-    #[serde(rename = "Cmd")]
-    cmd: Option<String>,
-    #[serde(rename = "Args")]
-    args: Option<String>,
-    #[serde(rename = "Iwd")]
-    iwd: Option<String>,
-    #[serde(rename = "UserLog")]
-    user_log: Option<String>,
-    #[serde(rename = "Err")]
-    err: Option<String>,
-    #[serde(rename = "Out")]
-    out: Option<String>,
-    #[allow(dead_code)]
-    #[serde(rename = "JobStartDate")]
-    job_start_date: Option<i64>,
-    #[serde(rename = "QDate")]
-    q_date: Option<i64>,
+use crate::cmd::condor::{
+    condor_history_for_user, condor_q_for_user, projection, JobAd, JobAttr, JobStatus,
+};
+use crate::cmd::list_jobs::JobFilter;
+use crate::utils::remote::{require_username, RemoteExecutor};
+use crate::utils::ssh::{build_path, shell_escape_single_quotes};
+
+// How often the remote follow script checks whether the job is still running
+const FOLLOW_POLL_SECS: u64 = 10;
+// Printed by the remote follow script once the job has left the running state
+const FOLLOW_END_MARKER: &str = "__MCT_FOLLOW_END__";
+
+/// Remote script that follows `files` with `tail -F` (surviving rotation and
+/// truncation) until job `job_id` is no longer running, then prints
+/// [`FOLLOW_END_MARKER`]. Everything runs in one SSH session.
+fn follow_command(job_id: &str, files: &[(String, String)], lines: i64) -> String {
+    let start = if lines == 0 {
+        "+1".to_string()
+    } else {
+        lines.to_string()
+    };
+    let paths = files
+        .iter()
+        .map(|(_, path)| format!("'{}'", shell_escape_single_quotes(path)))
+        .collect::<Vec<_>>()
+        .join(" ");
+    // Also stop polling if tail died, e.g. because the SSH connection went away
+    format!(
+        "tail -n {start} -F {paths} 2>&1 & t=$!; \
+         while kill -0 $t 2>/dev/null && [ \"$(condor_q {job_id} -af JobStatus 2>/dev/null)\" = 2 ]; do sleep {poll}; done; \
+         sleep 1; kill $t 2>/dev/null; echo {marker}",
+        start = start,
+        paths = paths,
+        job_id = job_id,
+        poll = FOLLOW_POLL_SECS,
+        marker = FOLLOW_END_MARKER,
+    )
+}
+
+/// Prefixes the interleaved `tail -F` output with the stream each line came
+/// from, using the `==> path <==` headers tail prints when it switches files.
+struct StreamLabeler {
+    files: Vec<(String, String)>,
+    current: String,
+    // tail separates sections with a blank line before each header
+    pending_blank: bool,
 }
 
-fn parse_job_selector(selector: &str) -> Option<(i32, Option<i32>)> {
+impl StreamLabeler {
+    fn new(files: &[(String, String)]) -> Self {
+        Self {
+            files: files.to_vec(),
+            current: files.first().map(|(l, _)| l.clone()).unwrap_or_default(),
+            pending_blank: false,
+        }
+    }
+
+    fn push(&mut self, line: &str) -> Vec<String> {
+        let mut out = Vec::new();
+        if let Some(path) = line
+            .strip_prefix("==> ")
+            .and_then(|rest| rest.strip_suffix(" <=="))
+        {
+            if let Some((label, _)) = self.files.iter().find(|(_, p)| p == path) {
+                self.current = label.clone();
+                self.pending_blank = false;
+                return out;
+            }
+        }
+        if self.pending_blank {
+            out.push(format!("[{}] ", self.current));
+            self.pending_blank = false;
+        }
+        if line.is_empty() {
+            self.pending_blank = true;
+        } else if line.starts_with("tail: ") {
+            out.push(format!("[tail] {}", line.trim_start_matches("tail: ")));
+        } else {
+            out.push(format!("[{}] {}", self.current, line));
+        }
+        out
+    }
+}
+
+pub fn parse_job_selector(selector: &str) -> Option<(i64, Option<i64>)> {
     if selector.eq_ignore_ascii_case("latest") || selector.eq_ignore_ascii_case("l") {
         return None;
     }
     if let Some((c, p)) = selector.split_once('.') {
-        if let Ok(cluster_id) = c.parse::<i32>() {
-            if let Ok(proc_id) = p.parse::<i32>() {
+        if let Ok(cluster_id) = c.parse::<i64>() {
+            if let Ok(proc_id) = p.parse::<i64>() {
                 return Some((cluster_id, Some(proc_id)));
             }
         }
     }
-    if let Ok(cluster_id) = selector.parse::<i32>() {
+    if let Ok(cluster_id) = selector.parse::<i64>() {
         return Some((cluster_id, None));
     }
     None
 }
 
-pub fn handle_logs(
-    selector: Option<String>,
-    show_out_only: bool,
-    show_log_only: bool,
-    show_err_only: bool,
-    num_lines: Option<i64>,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let config = ClusterConfig::load();
-    let login = match &config.login {
-        Some(l) => l,
-        None => {
-            eprintln!("Error: No login configuration found. Run 'mct login' first.");
-            std::process::exit(1);
-        }
-    };
+/// Which of a job's files `mct logs` shows; if none is set, all of them are shown.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LogFiles {
+    pub out: bool,
+    pub log: bool,
+    pub err: bool,
+}
 
-    // Fetch running jobs for the user
-    let username = config.get_username().ok_or_else(|| {
-        std::io::Error::new(
-            std::io::ErrorKind::Other,
-            "No username found in login or ssh config",
-        )
-    })?;
-
-    let attrs = [
-        "ClusterId",
-        "ProcId",
-        "Cmd",
-        "Args",
-        "Iwd",
-        "UserLog",
-        "Err",
-        "Out",
-        "JobStartDate",
-        "QDate",
-    ]
-    .join(",");
-    let condor_cmd = format!(
-        "condor_q {} -json -attributes {} -constraint 'JobStatus==2'",
-        username, attrs
-    );
+impl LogFiles {
+    fn any(&self) -> bool {
+        self.out || self.log || self.err
+    }
+}
 
-    let output = run_remote(login, &condor_cmd)?;
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("Failed to query jobs via SSH: {}", stderr).into());
+/// Labelled absolute paths of the selected files of `job`, skipping unset ones.
+/// Files shared by several streams (e.g. Out and Err) appear once, as `Out+Err`.
+pub fn job_files(job: &JobAd, files: LogFiles) -> Vec<(String, String)> {
+    let iwd = job.iwd.as_deref().unwrap_or(".");
+    let mut result: Vec<(String, String)> = Vec::new();
+    for (label, path, wanted) in [
+        ("Log", &job.user_log, files.log),
+        ("Out", &job.out, files.out),
+        ("Err", &job.err, files.err),
+    ] {
+        let path = path.as_deref().unwrap_or("");
+        if path.is_empty() || (files.any() && !wanted) {
+            continue;
+        }
+        let path_full = build_path(iwd, path);
+        match result.iter_mut().find(|(_, p)| *p == path_full) {
+            Some(existing) => existing.0 = format!("{}+{}", existing.0, label),
+            None => result.push((label.to_string(), path_full)),
+        }
     }
+    result
+}
 
-    // if output.stdout is empty, the user is not running any jobs
-    if output.stdout.is_empty() {
-        println!("No running jobs found for user '{}'.", username);
-        return Ok(());
+/// `-constraint` selecting a job or cluster and/or a set of states.
+fn selector_constraint(
+    selector: Option<(i64, Option<i64>)>,
+    statuses: &[JobStatus],
+) -> Option<String> {
+    let id_clause = selector.map(|(cid, pid)| match pid {
+        Some(pid) => format!("ClusterId == {} && ProcId == {}", cid, pid),
+        None => format!("ClusterId == {}", cid),
+    });
+    let status_clause = JobFilter {
+        statuses: statuses.to_vec(),
+        ..Default::default()
     }
+    .constraint();
+    let clauses: Vec<String> = id_clause.into_iter().chain(status_clause).collect();
+    (!clauses.is_empty()).then(|| clauses.join(" && "))
+}
 
-    let jobs: Vec<JobInfo> = parse_json_relaxed(&output.stdout)?;
-    if jobs.is_empty() {
-        println!("No running jobs found for user '{}'.", username);
-        return Ok(());
+// How many recent history entries are considered when looking for the latest job
+const LATEST_HISTORY_SCAN: usize = 10;
+// Upper bound on history entries scanned when selecting a whole cluster
+const CLUSTER_HISTORY_LIMIT: usize = 1000;
+
+fn in_history(status: JobStatus) -> bool {
+    matches!(status, JobStatus::Removed | JobStatus::Completed)
+}
+
+/// Find the selected job (or the most recently submitted one if `selector` is
+/// `None`) among queued jobs and, for finished ones, `condor_history`.
+pub fn find_job(
+    exec: &dyn RemoteExecutor,
+    username: &str,
+    attrs: &str,
+    selector: Option<(i64, Option<i64>)>,
+    statuses: &[JobStatus],
+) -> Result<Option<JobAd>, Box<dyn std::error::Error>> {
+    let constraint = selector_constraint(selector, statuses);
+    let want_queue = statuses.is_empty() || statuses.iter().any(|s| !in_history(*s));
+    let want_history = statuses.is_empty() || statuses.iter().any(|s| in_history(*s));
+
+    let mut candidates: Vec<JobAd> = Vec::new();
+    if want_queue {
+        candidates.extend(condor_q_for_user::<JobAd>(
+            exec,
+            username,
+            attrs,
+            constraint.as_deref(),
+        )?);
+    }
+    // A specific job that is still queued does not need a history lookup
+    if want_history && (selector.is_none() || candidates.is_empty()) {
+        let limit = if selector.is_some() {
+            1
+        } else {
+            LATEST_HISTORY_SCAN
+        };
+        candidates.extend(condor_history_for_user::<JobAd>(
+            exec,
+            username,
+            attrs,
+            limit,
+            constraint.as_deref(),
+        )?);
+    }
+
+    Ok(match selector {
+        Some(_) => candidates.into_iter().min_by_key(|j| j.proc_id),
+        None => candidates
+            .into_iter()
+            .max_by_key(|j| (j.q_date.unwrap_or_default(), j.cluster_id, j.proc_id)),
+    })
+}
+
+/// Jobs to act on: the last `last` history entries, every proc of a cluster,
+/// or a single job (the latest if no selector is given).
+pub fn find_jobs(
+    exec: &dyn RemoteExecutor,
+    username: &str,
+    attrs: &str,
+    selector: Option<(i64, Option<i64>)>,
+    last: Option<usize>,
+    statuses: &[JobStatus],
+) -> Result<Vec<JobAd>, Box<dyn std::error::Error>> {
+    if let Some(n) = last {
+        let constraint = selector_constraint(None, statuses);
+        return condor_history_for_user(exec, username, attrs, n, constraint.as_deref());
     }
+    match selector {
+        Some((cid, None)) => {
+            let constraint = selector_constraint(Some((cid, None)), statuses);
+            let mut jobs: Vec<JobAd> = Vec::new();
+            if statuses.is_empty() || statuses.iter().any(|s| !in_history(*s)) {
+                jobs.extend(condor_q_for_user::<JobAd>(
+                    exec,
+                    username,
+                    attrs,
+                    constraint.as_deref(),
+                )?);
+            }
+            if statuses.is_empty() || statuses.iter().any(|s| in_history(*s)) {
+                jobs.extend(condor_history_for_user::<JobAd>(
+                    exec,
+                    username,
+                    attrs,
+                    CLUSTER_HISTORY_LIMIT,
+                    constraint.as_deref(),
+                )?);
+            }
+            jobs.sort_by_key(|j| j.proc_id);
+            jobs.dedup_by_key(|j| j.proc_id);
+            Ok(jobs)
+        }
+        _ => Ok(find_job(exec, username, attrs, selector, statuses)?
+            .into_iter()
+            .collect()),
+    }
+}
+
+pub fn handle_logs(
+    exec: &dyn RemoteExecutor,
+    selector: Option<String>,
+    files: LogFiles,
+    num_lines: Option<i64>,
+    follow: bool,
+    statuses: &[JobStatus],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let username = require_username(exec)?;
+
+    let attrs = projection(&[
+        JobAttr::Cmd,
+        JobAttr::Args,
+        JobAttr::Iwd,
+        JobAttr::UserLog,
+        JobAttr::Err,
+        JobAttr::Out,
+        JobAttr::JobStatus,
+        JobAttr::JobStartDate,
+        JobAttr::QDate,
+    ]);
 
-    // Select job
-    let selected: &JobInfo = if let Some(sel) = selector.as_deref() {
-        match parse_job_selector(sel) {
-            Some((cid, maybe_pid)) => {
-                let mut iter = jobs.iter().filter(|j| j.cluster_id == cid);
-                if let Some(pid) = maybe_pid {
-                    iter.find(|j| j.proc_id == pid).ok_or_else(|| {
-                        format!("Job {}.{} not found among running jobs", cid, pid)
-                    })?
-                } else {
-                    iter.next()
-                        .ok_or_else(|| format!("Job {} not found among running jobs", cid))?
-                }
+    // Select job: an explicit id, else the latest job ('latest' or no selector)
+    let job_selector = selector.as_deref().and_then(parse_job_selector);
+    let Some(selected) = find_job(exec, &username, &attrs, job_selector, statuses)? else {
+        match job_selector {
+            Some((cid, Some(pid))) => {
+                return Err(format!("Job {}.{} not found in the queue or history", cid, pid).into())
             }
-            None => {
-                // 'latest' provided
-                jobs.iter()
-                    .max_by_key(|j| j.q_date.unwrap_or_default())
-                    .expect("non-empty")
+            Some((cid, None)) => {
+                return Err(format!("Job {} not found in the queue or history", cid).into())
             }
+            None => println!("No matching jobs found for user '{}'.", username),
         }
+        return Ok(());
+    };
+    let selected = &selected;
+
+    // Only running jobs have output to follow
+    let status = selected.status();
+    let follow = if follow && status != JobStatus::Running {
+        println!(
+            "Job {} is {}, showing its logs without following.",
+            selected.id(),
+            status.label()
+        );
+        false
     } else {
-        // default to latest
-        jobs.iter()
-            .max_by_key(|j| j.q_date.unwrap_or_default())
-            .expect("non-empty")
+        follow
     };
 
     let iwd = selected.iwd.as_deref().unwrap_or(".");
@@ -138,7 +310,7 @@ pub fn handle_logs(
     let out = selected.out.as_deref().unwrap_or("");
     let err = selected.err.as_deref().unwrap_or("");
 
-    let any_filter = show_out_only || show_log_only || show_err_only;
+    let any_filter = files.any();
     let effective_lines: i64 = match num_lines {
         Some(v) => v,
         None => {
@@ -155,15 +327,47 @@ pub fn handle_logs(
         effective_lines.to_string()
     };
     println!(
-        "Showing last {} lines for job {}.{}\nCmd: {} {}\nIwd: {}",
+        "{} last {} lines for job {}.{} ({})\nCmd: {} {}\nIwd: {}",
+        if follow { "Following from" } else { "Showing" },
         lines_desc,
         selected.cluster_id,
         selected.proc_id,
+        status.label(),
         selected.cmd.as_deref().unwrap_or(""),
         selected.args.as_deref().unwrap_or(""),
         iwd
     );
 
+    if follow {
+        let followed = job_files(selected, files);
+        if followed.is_empty() {
+            println!("No log files set for job {}.", selected.id());
+            return Ok(());
+        }
+        for (label, path) in &followed {
+            println!("[{}] {}", label, path);
+        }
+        println!();
+
+        let cmd = follow_command(&selected.id(), &followed, effective_lines);
+        let mut labeler = StreamLabeler::new(&followed);
+        let mut ended = false;
+        exec.stream(&cmd, &mut |line| {
+            if line == FOLLOW_END_MARKER {
+                ended = true;
+                return false;
+            }
+            for labeled in labeler.push(line) {
+                println!("{}", labeled);
+            }
+            true
+        })?;
+        if ended {
+            println!("\nJob {} is no longer running.", selected.id());
+        }
+        return Ok(());
+    }
+
     // Helper to tail a file remotely
     let show_file = |label: &str, path: &str| -> Result<(), Box<dyn std::error::Error>> {
         if path.is_empty() {
@@ -184,7 +388,7 @@ pub fn handle_logs(
                 effective_lines, path_esc, label, path_full
             )
         };
-        let out = run_remote(login, &cmd)?;
+        let out = exec.run(&cmd)?;
         println!(
             "\n== {}: {}\n{}",
             label,
@@ -194,15 +398,131 @@ pub fn handle_logs(
         Ok(())
     };
 
-    if !any_filter || show_log_only {
+    if !any_filter || files.log {
         show_file("Log", user_log)?;
     }
-    if !any_filter || show_out_only {
+    if !any_filter || files.out {
         show_file("Out", out)?;
     }
-    if !any_filter || show_err_only {
+    if !any_filter || files.err {
         show_file("Err", err)?;
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::remote::FixtureExecutor;
+
+    #[test]
+    fn parses_job_selectors() {
+        assert_eq!(parse_job_selector("latest"), None);
+        assert_eq!(parse_job_selector("42"), Some((42, None)));
+        assert_eq!(parse_job_selector("42.3"), Some((42, Some(3))));
+    }
+
+    #[test]
+    fn tails_log_files_of_latest_running_job() {
+        let out = "/lustre/home/barmstrong/ligoss/outputs/16337546.out";
+        let exec = FixtureExecutor::new("barmstrong")
+            .with_stdout(
+                "condor_q barmstrong -json -attributes ClusterId,ProcId,Cmd,Args,Iwd,UserLog,Err,Out,JobStatus,JobStartDate,QDate -constraint 'ClusterId == 16337546'",
+                include_str!("../../example.json"),
+            )
+            .with_stdout(
+                &format!("tail -n 20 '{}' || echo '[Out] file not found: {}'", out, out),
+                "epoch 1\n",
+            );
+        let files = LogFiles {
+            out: true,
+            ..Default::default()
+        };
+        handle_logs(&exec, Some("16337546".into()), files, Some(20), false, &[]).unwrap();
+        assert_eq!(exec.calls().len(), 2);
+    }
+
+    const ATTRS: &str =
+        "ClusterId,ProcId,Cmd,Args,Iwd,UserLog,Err,Out,JobStatus,JobStartDate,QDate";
+
+    #[test]
+    fn falls_back_to_history_for_finished_jobs() {
+        let finished =
+            include_str!("../../example.json").replace("\"JobStatus\": 2", "\"JobStatus\": 4");
+        let exec = FixtureExecutor::new("barmstrong")
+            .with_stdout(
+                &format!("condor_q barmstrong -json -attributes {} -constraint 'ClusterId == 16337546 && ProcId == 0'", ATTRS),
+                "",
+            )
+            .with_stdout(
+                &format!("condor_history barmstrong -json -attributes {} -limit 1 -constraint 'ClusterId == 16337546 && ProcId == 0'", ATTRS),
+                finished,
+            );
+        let job = find_job(&exec, "barmstrong", ATTRS, Some((16337546, Some(0))), &[])
+            .unwrap()
+            .unwrap();
+        assert_eq!(job.status(), JobStatus::Completed);
+        assert_eq!(exec.calls().len(), 2);
+    }
+
+    #[test]
+    fn latest_restricted_to_finished_states_only_queries_history() {
+        let exec = FixtureExecutor::new("barmstrong").with_stdout(
+            &format!(
+                "condor_history barmstrong -json -attributes {} -limit 10 -constraint '(JobStatus == 4)'",
+                ATTRS
+            ),
+            "[]",
+        );
+        let job = find_job(&exec, "barmstrong", ATTRS, None, &[JobStatus::Completed]).unwrap();
+        assert!(job.is_none());
+        assert_eq!(exec.calls().len(), 1);
+    }
+
+    #[test]
+    fn follow_script_tails_all_files_until_job_stops() {
+        let files = vec![
+            ("Out".to_string(), "/scratch/o'ut.txt".to_string()),
+            ("Err".to_string(), "/scratch/err.txt".to_string()),
+        ];
+        let cmd = follow_command("42.0", &files, 0);
+        assert!(cmd.starts_with("tail -n +1 -F '/scratch/o'\\''ut.txt' '/scratch/err.txt' 2>&1 &"));
+        assert!(cmd.contains("condor_q 42.0 -af JobStatus"));
+        assert!(cmd.ends_with(FOLLOW_END_MARKER));
+    }
+
+    #[test]
+    fn labels_interleaved_tail_output() {
+        let files = vec![
+            ("Out".to_string(), "/o.txt".to_string()),
+            ("Err".to_string(), "/e.txt".to_string()),
+        ];
+        let mut labeler = StreamLabeler::new(&files);
+        let lines: Vec<String> = [
+            "==> /o.txt <==",
+            "epoch 1",
+            "",
+            "==> /e.txt <==",
+            "warning",
+            "tail: /o.txt: file truncated",
+            "",
+            "",
+            "==> /o.txt <==",
+            "epoch 2",
+        ]
+        .iter()
+        .flat_map(|l| labeler.push(l))
+        .collect();
+        assert_eq!(
+            lines,
+            vec![
+                "[Out] epoch 1",
+                "[Err] warning",
+                "[tail] /o.txt: file truncated",
+                "[Err] ",
+                "[Out] epoch 2"
+            ]
+        );
+    }
+}
@@ -1,14 +1,30 @@
+pub mod bid;
+pub mod columns;
 pub mod condor;
+pub mod events;
+pub mod fetch;
+pub mod grep;
 pub mod hist;
+pub mod job_actions;
+pub mod job_detail;
 pub mod jobs;
 pub mod list_jobs;
+pub mod log_view;
 pub mod login;
 pub mod logs;
 pub mod price;
+pub mod price_trend;
+pub mod ssh;
 
+pub use bid::handle_bid_suggest;
+pub use events::handle_events;
+pub use fetch::handle_fetch;
+pub use grep::handle_log_grep;
 pub use hist::handle_hist;
 pub use jobs::handle_jobs;
 pub use list_jobs::handle_list_jobs;
 pub use login::handle_login;
 pub use logs::handle_logs;
 pub use price::handle_price;
+pub use price_trend::handle_price_trend;
+pub use ssh::{handle_ssh_close, handle_ssh_status};
@@ -1,22 +1,14 @@
-use crate::config::ClusterConfig;
-use crate::utils::serde::deserialize_request_gpus;
-use crate::utils::ssh::{parse_json_relaxed, run_remote};
+use crate::cmd::columns::format_mb;
+use crate::cmd::condor::{projection, JobAd, JobAttr, JobStatus};
+use crate::cmd::price_trend::{append_snapshot, BucketStats, PriceSnapshot};
+use crate::utils::output::{OutputFormat, Records};
+use crate::utils::remote::RemoteExecutor;
+use crate::utils::ssh::parse_json_relaxed;
+use crate::utils::time::now_unix;
 use comfy_table::{presets::UTF8_FULL, Attribute, Cell, Color, ContentArrangement, Table};
-use serde::Deserialize;
-
-#[derive(Deserialize, Debug)]
-struct Job {
-    #[serde(rename = "JobPrio")]
-    job_prio: i32,
-    #[serde(rename = "JobStatus")]
-    job_status: i32,
-    #[serde(
-        rename = "RequestGPUs",
-        default,
-        deserialize_with = "deserialize_request_gpus"
-    )]
-    request_gpus: i32,
-}
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::Path;
 
 #[derive(Debug)]
 struct PriceStats {
@@ -26,6 +18,9 @@ struct PriceStats {
     avg_price: f64,
     avg_idle_price: f64,
     avg_running_price: f64,
+    price_dist: Option<Distribution>,
+    idle_price_dist: Option<Distribution>,
+    running_price_dist: Option<Distribution>,
 }
 
 impl PriceStats {
@@ -37,174 +32,371 @@ impl PriceStats {
             avg_price: 0.0,
             avg_idle_price: 0.0,
             avg_running_price: 0.0,
+            price_dist: None,
+            idle_price_dist: None,
+            running_price_dist: None,
         }
     }
 }
 
-fn job_prio_to_price(job_prio: i32) -> f64 {
-    // Convert JobPrio range [-1000, 1000] to price range [0, 2000]
-    (job_prio + 1000) as f64
+/// Order statistics of a set of bids.
+#[derive(Debug, Clone, PartialEq)]
+struct Distribution {
+    min: f64,
+    median: f64,
+    p75: f64,
+    p90: f64,
+    max: f64,
+}
+
+/// Percentile `p` (0-1) of `sorted`, interpolating linearly between neighbours.
+pub fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let rank = p * (sorted.len() - 1) as f64;
+    let (lo, hi) = (rank.floor() as usize, rank.ceil() as usize);
+    sorted[lo] + (sorted[hi] - sorted[lo]) * (rank - lo as f64)
+}
+
+fn distribution(prices: impl Iterator<Item = f64>) -> Option<Distribution> {
+    let mut sorted: Vec<f64> = prices.collect();
+    if sorted.is_empty() {
+        return None;
+    }
+    sorted.sort_by(f64::total_cmp);
+    Some(Distribution {
+        min: sorted[0],
+        median: percentile(&sorted, 0.5),
+        p75: percentile(&sorted, 0.75),
+        p90: percentile(&sorted, 0.9),
+        max: sorted[sorted.len() - 1],
+    })
+}
+
+// Bids are whole numbers, but percentiles can fall between two of them
+pub fn format_price(price: f64) -> String {
+    if price.fract() == 0.0 {
+        format!("{:.0}", price)
+    } else {
+        format!("{:.1}", price)
+    }
+}
+
+fn right(text: String) -> Cell {
+    Cell::new(text).set_alignment(comfy_table::CellAlignment::Right)
+}
+
+fn bold_right(text: &str) -> Cell {
+    Cell::new(text)
+        .add_attribute(Attribute::Bold)
+        .set_alignment(comfy_table::CellAlignment::Right)
+}
+
+const DISTRIBUTION_HEADERS: [&str; 5] = ["Min", "Median", "P75", "P90", "Max"];
+
+fn distribution_cells(dist: Option<&Distribution>) -> Vec<Cell> {
+    match dist {
+        Some(d) => [d.min, d.median, d.p75, d.p90, d.max]
+            .into_iter()
+            .map(|p| right(format_price(p)))
+            .collect(),
+        None => (0..DISTRIBUTION_HEADERS.len())
+            .map(|_| right("-".to_string()).fg(Color::DarkGrey))
+            .collect(),
+    }
 }
 
 fn create_combined_stats_table(gpu_stats: &PriceStats, cpu_stats: &PriceStats) -> Table {
     let mut table = Table::new();
+    let mut header = vec![
+        Cell::new("Job Type").add_attribute(Attribute::Bold),
+        Cell::new("Status").add_attribute(Attribute::Bold),
+        bold_right("Count"),
+        bold_right("Average Price"),
+    ];
+    header.extend(DISTRIBUTION_HEADERS.iter().map(|h| bold_right(h)));
     table
         .load_preset(UTF8_FULL)
         .set_content_arrangement(ContentArrangement::Dynamic)
-        .set_header(vec![
-            Cell::new("Job Type").add_attribute(Attribute::Bold),
-            Cell::new("Status").add_attribute(Attribute::Bold),
-            Cell::new("Count")
-                .add_attribute(Attribute::Bold)
-                .set_alignment(comfy_table::CellAlignment::Right),
-            Cell::new("Average Price")
-                .add_attribute(Attribute::Bold)
-                .set_alignment(comfy_table::CellAlignment::Right),
-        ]);
-
-    // GPU Jobs
-    if gpu_stats.total_jobs > 0 {
-        table.add_row(vec![
-            Cell::new("GPU").fg(Color::Green),
-            Cell::new("Total"),
-            Cell::new(gpu_stats.total_jobs.to_string())
-                .fg(Color::Green)
-                .set_alignment(comfy_table::CellAlignment::Right),
-            Cell::new(format!("{:.2}", gpu_stats.avg_price))
-                .set_alignment(comfy_table::CellAlignment::Right),
-        ]);
-
-        table.add_row(vec![
-            Cell::new(""),
-            Cell::new("Idle"),
-            Cell::new(gpu_stats.idle_jobs.to_string())
-                .fg(Color::Blue)
-                .set_alignment(comfy_table::CellAlignment::Right),
-            Cell::new(if gpu_stats.idle_jobs > 0 {
-                format!("{:.2}", gpu_stats.avg_idle_price)
-            } else {
-                "N/A".to_string()
-            })
-            .set_alignment(comfy_table::CellAlignment::Right),
-        ]);
-
-        table.add_row(vec![
-            Cell::new(""),
-            Cell::new("Running"),
-            Cell::new(gpu_stats.running_jobs.to_string())
-                .fg(Color::Magenta)
-                .set_alignment(comfy_table::CellAlignment::Right),
-            Cell::new(if gpu_stats.running_jobs > 0 {
-                format!("{:.2}", gpu_stats.avg_running_price)
-            } else {
-                "N/A".to_string()
-            })
-            .set_alignment(comfy_table::CellAlignment::Right),
-        ]);
-    } else {
-        table.add_row(vec![
-            Cell::new("GPU").fg(Color::Green),
-            Cell::new("No jobs found"),
-            Cell::new("-")
-                .fg(Color::DarkGrey)
-                .set_alignment(comfy_table::CellAlignment::Right),
-            Cell::new("-")
-                .fg(Color::DarkGrey)
-                .set_alignment(comfy_table::CellAlignment::Right),
-        ]);
+        .set_header(header);
+
+    for (label, label_color, stats) in [
+        ("GPU", Color::Green, gpu_stats),
+        ("CPU", Color::Blue, cpu_stats),
+    ] {
+        if stats.total_jobs == 0 {
+            let mut row = vec![
+                Cell::new(label).fg(label_color),
+                Cell::new("No jobs found"),
+                right("-".to_string()).fg(Color::DarkGrey),
+                right("-".to_string()).fg(Color::DarkGrey),
+            ];
+            row.extend(distribution_cells(None));
+            table.add_row(row);
+            continue;
+        }
+        for (i, (status, count, count_color, avg, dist)) in [
+            (
+                "Total",
+                stats.total_jobs,
+                Color::Green,
+                stats.avg_price,
+                &stats.price_dist,
+            ),
+            (
+                "Idle",
+                stats.idle_jobs,
+                Color::Blue,
+                stats.avg_idle_price,
+                &stats.idle_price_dist,
+            ),
+            (
+                "Running",
+                stats.running_jobs,
+                Color::Magenta,
+                stats.avg_running_price,
+                &stats.running_price_dist,
+            ),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let mut row = vec![
+                if i == 0 {
+                    Cell::new(label).fg(label_color)
+                } else {
+                    Cell::new("")
+                },
+                Cell::new(status),
+                right(count.to_string()).fg(count_color),
+                right(if count > 0 {
+                    format!("{:.2}", avg)
+                } else {
+                    "N/A".to_string()
+                }),
+            ];
+            row.extend(distribution_cells(dist.as_ref()));
+            table.add_row(row);
+        }
     }
 
-    // CPU Jobs
-    if cpu_stats.total_jobs > 0 {
-        table.add_row(vec![
-            Cell::new("CPU").fg(Color::Blue),
-            Cell::new("Total"),
-            Cell::new(cpu_stats.total_jobs.to_string())
-                .fg(Color::Green)
-                .set_alignment(comfy_table::CellAlignment::Right),
-            Cell::new(format!("{:.2}", cpu_stats.avg_price))
-                .set_alignment(comfy_table::CellAlignment::Right),
-        ]);
-
-        table.add_row(vec![
-            Cell::new(""),
-            Cell::new("Idle"),
-            Cell::new(cpu_stats.idle_jobs.to_string())
-                .fg(Color::Blue)
-                .set_alignment(comfy_table::CellAlignment::Right),
-            Cell::new(if cpu_stats.idle_jobs > 0 {
-                format!("{:.2}", cpu_stats.avg_idle_price)
-            } else {
-                "N/A".to_string()
-            })
-            .set_alignment(comfy_table::CellAlignment::Right),
-        ]);
-
-        table.add_row(vec![
-            Cell::new(""),
-            Cell::new("Running"),
-            Cell::new(cpu_stats.running_jobs.to_string())
-                .fg(Color::Magenta)
-                .set_alignment(comfy_table::CellAlignment::Right),
-            Cell::new(if cpu_stats.running_jobs > 0 {
-                format!("{:.2}", cpu_stats.avg_running_price)
-            } else {
-                "N/A".to_string()
-            })
-            .set_alignment(comfy_table::CellAlignment::Right),
-        ]);
-    } else {
-        table.add_row(vec![
-            Cell::new("CPU").fg(Color::Blue),
-            Cell::new("No jobs found"),
-            Cell::new("-")
-                .fg(Color::DarkGrey)
-                .set_alignment(comfy_table::CellAlignment::Right),
-            Cell::new("-")
-                .fg(Color::DarkGrey)
-                .set_alignment(comfy_table::CellAlignment::Right),
-        ]);
+    table
+}
+
+/// Bids of the jobs sharing one value of a breakdown dimension.
+#[derive(Debug)]
+struct PriceGroup {
+    label: String,
+    /// Value for the machine-readable formats
+    key: String,
+    idle_jobs: usize,
+    running_jobs: usize,
+    total_jobs: usize,
+    avg_price: f64,
+    dist: Distribution,
+}
+
+/// Group GPU jobs by `key` (sorted), e.g. the requested GPU count.
+fn group_gpu_jobs<K: Ord + Copy>(
+    jobs: &[JobAd],
+    key: impl Fn(&JobAd) -> K,
+    label: impl Fn(K) -> (String, String),
+) -> Vec<PriceGroup> {
+    let mut groups: BTreeMap<K, Vec<&JobAd>> = BTreeMap::new();
+    for job in jobs.iter().filter(|j| j.gpus() > 0) {
+        groups.entry(key(job)).or_default().push(job);
     }
+    groups
+        .into_iter()
+        .filter_map(|(k, members)| {
+            let (label, key) = label(k);
+            Some(PriceGroup {
+                label,
+                key,
+                idle_jobs: members
+                    .iter()
+                    .filter(|j| j.status() == JobStatus::Idle)
+                    .count(),
+                running_jobs: members
+                    .iter()
+                    .filter(|j| j.status() == JobStatus::Running)
+                    .count(),
+                total_jobs: members.len(),
+                avg_price: members.iter().map(|j| j.price()).sum::<f64>() / members.len() as f64,
+                dist: distribution(members.iter().map(|j| j.price()))?,
+            })
+        })
+        .collect()
+}
+
+fn gpu_count_groups(jobs: &[JobAd]) -> Vec<PriceGroup> {
+    group_gpu_jobs(
+        jobs,
+        |j| j.gpus(),
+        |gpus| {
+            (
+                format!("{} GPU{}", gpus, if gpus == 1 { "" } else { "s" }),
+                gpus.to_string(),
+            )
+        },
+    )
+}
 
+fn gpu_memory_groups(jobs: &[JobAd]) -> Vec<PriceGroup> {
+    group_gpu_jobs(
+        jobs,
+        |j| j.gpu_memory_requirement(),
+        |mb| match mb {
+            Some(mb) => (format!(">= {}", format_mb(mb)), mb.to_string()),
+            None => ("any".to_string(), String::new()),
+        },
+    )
+}
+
+fn create_breakdown_table(dimension: &str, groups: &[PriceGroup]) -> Table {
+    let mut table = Table::new();
+    let mut header = vec![
+        Cell::new(dimension).add_attribute(Attribute::Bold),
+        bold_right("Count"),
+        bold_right("Idle"),
+        bold_right("Running"),
+        bold_right("Average Price"),
+    ];
+    header.extend(DISTRIBUTION_HEADERS.iter().map(|h| bold_right(h)));
+    table
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(header);
+    for group in groups {
+        let mut row = vec![
+            Cell::new(&group.label).fg(Color::Green),
+            right(group.total_jobs.to_string()),
+            right(group.idle_jobs.to_string()).fg(Color::Blue),
+            right(group.running_jobs.to_string()).fg(Color::Magenta),
+            right(format!("{:.2}", group.avg_price)),
+        ];
+        row.extend(distribution_cells(Some(&group.dist)));
+        table.add_row(row);
+    }
     table
 }
 
-fn calculate_stats(jobs: &[Job], has_gpu: bool) -> PriceStats {
-    let filtered_jobs: Vec<&Job> = jobs
+// Width of the longest histogram bar, in characters
+const HISTOGRAM_WIDTH: usize = 40;
+// Approximate number of histogram bins
+const HISTOGRAM_BINS: f64 = 12.0;
+
+/// Jobs with bids in `[low, low + width)`.
+#[derive(Debug, PartialEq)]
+struct Bin {
+    low: f64,
+    running: usize,
+    other: usize,
+}
+
+/// A round bin width (1, 2 or 5 times a power of ten) giving about [`HISTOGRAM_BINS`] bins.
+fn bin_width(min: f64, max: f64) -> f64 {
+    let raw = ((max - min) / HISTOGRAM_BINS).max(1.0);
+    let magnitude = 10f64.powf(raw.log10().floor());
+    [1.0, 2.0, 5.0, 10.0]
+        .iter()
+        .map(|m| m * magnitude)
+        .find(|w| *w >= raw)
+        .unwrap_or(10.0 * magnitude)
+}
+
+fn histogram(jobs: &[JobAd]) -> (f64, Vec<Bin>) {
+    let prices: Vec<f64> = jobs.iter().map(|j| j.price()).collect();
+    let (Some(min), Some(max)) = (
+        prices.iter().copied().reduce(f64::min),
+        prices.iter().copied().reduce(f64::max),
+    ) else {
+        return (1.0, Vec::new());
+    };
+    let width = bin_width(min, max);
+    let first = (min / width).floor() * width;
+    let count = ((max - first) / width).floor() as usize + 1;
+    let mut bins: Vec<Bin> = (0..count)
+        .map(|i| Bin {
+            low: first + i as f64 * width,
+            running: 0,
+            other: 0,
+        })
+        .collect();
+    for job in jobs {
+        let bin = &mut bins[((job.price() - first) / width).floor() as usize];
+        if job.status() == JobStatus::Running {
+            bin.running += 1;
+        } else {
+            bin.other += 1;
+        }
+    }
+    (width, bins)
+}
+
+fn render_histogram(width: f64, bins: &[Bin]) -> String {
+    let largest = bins.iter().map(|b| b.running + b.other).max().unwrap_or(0);
+    if largest == 0 {
+        return String::new();
+    }
+    let scale = |n: usize| (n * HISTOGRAM_WIDTH).div_ceil(largest);
+    let labels: Vec<String> = bins
+        .iter()
+        .map(|b| {
+            if width > 1.0 {
+                format!(
+                    "{}-{}",
+                    format_price(b.low),
+                    format_price(b.low + width - 1.0)
+                )
+            } else {
+                format_price(b.low)
+            }
+        })
+        .collect();
+    let label_w = labels.iter().map(|l| l.len()).max().unwrap_or(0);
+    let mut out = String::new();
+    for (bin, label) in bins.iter().zip(&labels) {
+        let total = bin.running + bin.other;
+        let running = scale(bin.running);
+        let bar = scale(total).max(running);
+        out.push_str(&format!(
+            "{:>label_w$} │{}{} {}\n",
+            label,
+            "█".repeat(running),
+            "░".repeat(bar - running),
+            total,
+            label_w = label_w
+        ));
+    }
+    out
+}
+
+fn calculate_stats(jobs: &[JobAd], has_gpu: bool) -> PriceStats {
+    let filtered_jobs: Vec<&JobAd> = jobs
         .iter()
-        .filter(|job| (job.request_gpus > 0) == has_gpu)
+        .filter(|job| (job.gpus() > 0) == has_gpu)
         .collect();
 
     if filtered_jobs.is_empty() {
         return PriceStats::new();
     }
 
-    let idle_jobs: Vec<&Job> = filtered_jobs
+    let idle_jobs: Vec<&JobAd> = filtered_jobs
         .iter()
-        .filter(|job| job.job_status == 1) // Idle
+        .filter(|job| job.status() == JobStatus::Idle)
         .copied()
         .collect();
 
-    let running_jobs: Vec<&Job> = filtered_jobs
+    let running_jobs: Vec<&JobAd> = filtered_jobs
         .iter()
-        .filter(|job| job.job_status == 2) // Running
+        .filter(|job| job.status() == JobStatus::Running)
         .copied()
         .collect();
 
-    let total_price: f64 = filtered_jobs
-        .iter()
-        .map(|job| job_prio_to_price(job.job_prio))
-        .sum();
+    let total_price: f64 = filtered_jobs.iter().map(|job| job.price()).sum();
 
-    let idle_price: f64 = idle_jobs
-        .iter()
-        .map(|job| job_prio_to_price(job.job_prio))
-        .sum();
+    let idle_price: f64 = idle_jobs.iter().map(|job| job.price()).sum();
 
-    let running_price: f64 = running_jobs
-        .iter()
-        .map(|job| job_prio_to_price(job.job_prio))
-        .sum();
+    let running_price: f64 = running_jobs.iter().map(|job| job.price()).sum();
 
     PriceStats {
         total_jobs: filtered_jobs.len(),
@@ -225,50 +417,298 @@ fn calculate_stats(jobs: &[Job], has_gpu: bool) -> PriceStats {
         } else {
             running_price / running_jobs.len() as f64
         },
+        price_dist: distribution(filtered_jobs.iter().map(|job| job.price())),
+        idle_price_dist: distribution(idle_jobs.iter().map(|job| job.price())),
+        running_price_dist: distribution(running_jobs.iter().map(|job| job.price())),
     }
 }
 
-pub fn handle_price() -> Result<(), Box<dyn std::error::Error>> {
-    let config = ClusterConfig::load();
+fn distribution_values(dist: Option<&Distribution>) -> Vec<Value> {
+    match dist {
+        Some(d) => [d.min, d.median, d.p75, d.p90, d.max]
+            .into_iter()
+            .map(|p| json!(p))
+            .collect(),
+        None => vec![Value::Null; DISTRIBUTION_HEADERS.len()],
+    }
+}
+
+// Count, average (if any jobs) and distribution per status, "total" first
+fn status_buckets(
+    stats: &PriceStats,
+) -> [(&'static str, usize, Option<f64>, Option<&Distribution>); 3] {
+    let avg = |count: usize, avg: f64| if count > 0 { Some(avg) } else { None };
+    [
+        (
+            "total",
+            stats.total_jobs,
+            avg(stats.total_jobs, stats.avg_price),
+            stats.price_dist.as_ref(),
+        ),
+        (
+            "idle",
+            stats.idle_jobs,
+            avg(stats.idle_jobs, stats.avg_idle_price),
+            stats.idle_price_dist.as_ref(),
+        ),
+        (
+            "running",
+            stats.running_jobs,
+            avg(stats.running_jobs, stats.avg_running_price),
+            stats.running_price_dist.as_ref(),
+        ),
+    ]
+}
 
-    // Check if login configuration exists
-    let login = match &config.login {
-        Some(l) => l,
-        None => {
-            eprintln!("Error: No login configuration found.");
-            eprintln!("Please run 'cluster login' first to configure your connection settings.");
-            std::process::exit(1);
+/// The per-bucket statistics as stored by `mct price --record`.
+fn snapshot(gpu_stats: &PriceStats, cpu_stats: &PriceStats, time: i64) -> PriceSnapshot {
+    let mut buckets = Vec::new();
+    for (job_type, stats) in [("gpu", gpu_stats), ("cpu", cpu_stats)] {
+        for (status, count, avg, dist) in status_buckets(stats) {
+            buckets.push(BucketStats {
+                job_type: job_type.to_string(),
+                status: status.to_string(),
+                count,
+                avg_price: avg,
+                min_price: dist.map(|d| d.min),
+                median_price: dist.map(|d| d.median),
+                p75_price: dist.map(|d| d.p75),
+                p90_price: dist.map(|d| d.p90),
+                max_price: dist.map(|d| d.max),
+            });
         }
-    };
+    }
+    PriceSnapshot { time, buckets }
+}
 
-    println!("Connecting to cluster and fetching job data...");
+/// One row per job type and status, then per GPU count (`group_by` = `gpus`) and
+/// minimum GPU memory in MB (`group_by` = `gpu_memory_mb`, empty group for none).
+fn stats_records(
+    gpu_stats: &PriceStats,
+    cpu_stats: &PriceStats,
+    breakdowns: &[(&str, &[PriceGroup])],
+) -> Records {
+    let mut records = Records::new(vec![
+        "job_type",
+        "status",
+        "count",
+        "avg_price",
+        "min_price",
+        "median_price",
+        "p75_price",
+        "p90_price",
+        "max_price",
+        "group_by",
+        "group",
+    ]);
+    for (job_type, stats) in [("gpu", gpu_stats), ("cpu", cpu_stats)] {
+        for (status, count, avg, dist) in status_buckets(stats) {
+            let mut row = vec![json!(job_type), json!(status), json!(count), json!(avg)];
+            row.extend(distribution_values(dist));
+            row.extend([json!(""), json!("")]);
+            records.push(row);
+        }
+    }
+    for (group_by, groups) in breakdowns {
+        for group in *groups {
+            let mut row = vec![
+                json!("gpu"),
+                json!("total"),
+                json!(group.total_jobs),
+                json!(group.avg_price),
+            ];
+            row.extend(distribution_values(Some(&group.dist)));
+            row.extend([json!(group_by), json!(group.key)]);
+            records.push(row);
+        }
+    }
+    records
+}
 
-    let attrs = "JobPrio,JobStatus,RequestGPUs";
+/// Print the queue's bid statistics and, with `record`, append them to that history file.
+pub fn handle_price(
+    exec: &dyn RemoteExecutor,
+    output: OutputFormat,
+    record: Option<&Path>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if output.is_table() {
+        println!("Connecting to cluster and fetching job data...");
+    }
+
+    let attrs = projection(&[
+        JobAttr::JobPrio,
+        JobAttr::JobStatus,
+        JobAttr::RequestGpus,
+        JobAttr::Requirements,
+    ]);
     let condor_cmd = format!("condor_q -json -attributes {}", attrs);
 
-    let output = run_remote(login, &condor_cmd)?;
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
+    let out = exec.run(&condor_cmd)?;
+    if !out.success() {
+        let stderr = String::from_utf8_lossy(&out.stderr);
         return Err(format!("SSH command failed: {}", stderr).into());
     }
 
-    let stdout = &output.stdout;
+    let stdout = &out.stdout;
 
     // Parse JSON response (relaxed)
-    let jobs: Vec<Job> = parse_json_relaxed(stdout)
-        .map_err(|e| format!("Failed to parse JSON response: {}", e))?;
+    let jobs: Vec<JobAd> =
+        parse_json_relaxed(stdout).map_err(|e| format!("Failed to parse JSON response: {}", e))?;
+
+    // Calculate statistics
+    let gpu_stats = calculate_stats(&jobs, true);
+    let no_gpu_stats = calculate_stats(&jobs, false);
+    let by_gpus = gpu_count_groups(&jobs);
+    let by_memory = gpu_memory_groups(&jobs);
+
+    if let Some(path) = record {
+        append_snapshot(path, &snapshot(&gpu_stats, &no_gpu_stats, now_unix()))
+            .map_err(|e| format!("Failed to record snapshot to {}: {}", path.display(), e))?;
+        // Keep machine-readable output clean
+        eprintln!("Recorded snapshot to {}", path.display());
+    }
+
+    if !output.is_table() {
+        let breakdowns: [(&str, &[PriceGroup]); 2] =
+            [("gpus", &by_gpus), ("gpu_memory_mb", &by_memory)];
+        print!(
+            "{}",
+            stats_records(&gpu_stats, &no_gpu_stats, &breakdowns).render(output)
+        );
+        return Ok(());
+    }
 
     if jobs.is_empty() {
         println!("No jobs found in the queue.");
         return Ok(());
     }
 
-    // Calculate statistics
-    let gpu_stats = calculate_stats(&jobs, true);
-    let no_gpu_stats = calculate_stats(&jobs, false);
-
     let combined_table = create_combined_stats_table(&gpu_stats, &no_gpu_stats);
     println!("{}", combined_table);
 
+    let (width, bins) = histogram(&jobs);
+    println!("\nBid distribution (█ running, ░ idle/held):");
+    print!("{}", render_histogram(width, &bins));
+
+    if !by_gpus.is_empty() {
+        println!("\nGPU jobs by requested GPUs:");
+        println!("{}", create_breakdown_table("GPUs", &by_gpus));
+        println!("\nGPU jobs by minimum GPU memory (from Requirements):");
+        println!("{}", create_breakdown_table("GPU Memory", &by_memory));
+    }
+
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = include_str!("../../example.json");
+
+    fn job(prio: i64, status: i64, gpus: i64, requirements: &str) -> JobAd {
+        serde_json::from_value(json!({
+            "ClusterId": 1,
+            "ProcId": 0,
+            "JobPrio": prio,
+            "JobStatus": status,
+            "RequestGPUs": gpus,
+            "Requirements": format!("/Expr({})/", requirements),
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn stats_from_captured_queue() {
+        let jobs: Vec<JobAd> = parse_json_relaxed(EXAMPLE.as_bytes()).unwrap();
+        let gpu = calculate_stats(&jobs, true);
+        assert_eq!(gpu.total_jobs, 1);
+        assert_eq!(gpu.running_jobs, 1);
+        assert_eq!(gpu.avg_running_price, 250.0);
+        assert_eq!(gpu.running_price_dist.as_ref().unwrap().p90, 250.0);
+        let cpu = calculate_stats(&jobs, false);
+        assert_eq!(cpu.total_jobs, 0);
+        let recorded = snapshot(&gpu, &cpu, 1754658216);
+        assert_eq!(recorded.buckets.len(), 6);
+        assert_eq!(
+            (
+                recorded.buckets[2].status.as_str(),
+                recorded.buckets[2].median_price
+            ),
+            ("running", Some(250.0))
+        );
+        assert_eq!(recorded.buckets[3].avg_price, None);
+
+        let by_memory = gpu_memory_groups(&jobs);
+        assert_eq!(by_memory[0].label, ">= 58.6G");
+        let breakdowns: [(&str, &[PriceGroup]); 1] = [("gpu_memory_mb", &by_memory)];
+        let csv = stats_records(&gpu, &cpu, &breakdowns).render(OutputFormat::Csv);
+        assert!(csv.starts_with(
+            "job_type,status,count,avg_price,min_price,median_price,p75_price,p90_price,max_price,group_by,group\ngpu,total,1,250.0,250.0,250.0,250.0,250.0,250.0,,\n"
+        ));
+        assert!(csv.contains("cpu,running,0,,,,,,,,\n"));
+        assert!(
+            csv.ends_with("gpu,total,1,250.0,250.0,250.0,250.0,250.0,250.0,gpu_memory_mb,60000\n")
+        );
+    }
+
+    #[test]
+    fn percentiles_and_breakdowns() {
+        let memory = "(TARGET.CUDAGlobalMemoryMb > 60000) && (TARGET.GPUs >= RequestGPUs)";
+        let jobs = vec![
+            job(-900, 2, 1, "TARGET.GPUs >= RequestGPUs"),
+            job(-800, 1, 1, "TARGET.GPUs >= RequestGPUs"),
+            job(-700, 2, 2, memory),
+            job(-600, 1, 2, memory),
+            job(-500, 1, 8, memory),
+            job(0, 1, 0, "true"),
+        ];
+        let gpu = calculate_stats(&jobs, true);
+        assert_eq!(
+            gpu.price_dist,
+            Some(Distribution {
+                min: 100.0,
+                median: 300.0,
+                p75: 400.0,
+                p90: 460.0,
+                max: 500.0,
+            })
+        );
+
+        let by_gpus = gpu_count_groups(&jobs);
+        let labels: Vec<&str> = by_gpus.iter().map(|g| g.label.as_str()).collect();
+        assert_eq!(labels, vec!["1 GPU", "2 GPUs", "8 GPUs"]);
+        assert_eq!((by_gpus[1].idle_jobs, by_gpus[1].running_jobs), (1, 1));
+        assert_eq!(by_gpus[1].dist.median, 350.0);
+
+        let by_memory = gpu_memory_groups(&jobs);
+        assert_eq!(by_memory[0].label, "any");
+        assert_eq!(by_memory[1].total_jobs, 3);
+        assert_eq!(by_memory[1].key, "60000");
+    }
+
+    #[test]
+    fn histogram_uses_round_bins() {
+        assert_eq!(bin_width(100.0, 500.0), 50.0);
+        assert_eq!(bin_width(250.0, 250.0), 1.0);
+        let jobs = vec![
+            job(-900, 2, 1, "true"),
+            job(-880, 1, 1, "true"),
+            job(-760, 1, 1, "true"),
+        ];
+        let (width, bins) = histogram(&jobs);
+        assert_eq!(width, 20.0);
+        assert_eq!(bins.len(), 8);
+        assert_eq!(
+            bins[0],
+            Bin {
+                low: 100.0,
+                running: 1,
+                other: 0
+            }
+        );
+        let rendered = render_histogram(width, &bins);
+        assert!(rendered.starts_with("100-119 │████████████████████████████████████████ 1\n"));
+        assert!(rendered.ends_with("240-259 │░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░░ 1\n"));
+    }
+}
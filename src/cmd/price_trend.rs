@@ -0,0 +1,419 @@
+//! Local history of `mct price --record` snapshots and the `mct price trend` view.
+
+use crate::cmd::columns::format_unix;
+use crate::cmd::price::format_price;
+use crate::utils::output::{OutputFormat, Records};
+use crate::utils::time::{human_duration, now_unix};
+use comfy_table::{presets::UTF8_FULL, Attribute, Cell, Color, ContentArrangement, Table};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::Path;
+
+const SPARK_CHARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+// Characters in the over-time sparklines
+const TREND_WIDTH: usize = 32;
+
+/// Buckets shown by `mct price trend`; snapshots also hold the totals.
+const TREND_BUCKETS: [(&str, &str); 4] = [
+    ("gpu", "idle"),
+    ("gpu", "running"),
+    ("cpu", "idle"),
+    ("cpu", "running"),
+];
+
+/// Bid statistics of one job type and status, as in `mct price --output json`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BucketStats {
+    pub job_type: String,
+    pub status: String,
+    pub count: usize,
+    pub avg_price: Option<f64>,
+    pub min_price: Option<f64>,
+    pub median_price: Option<f64>,
+    pub p75_price: Option<f64>,
+    pub p90_price: Option<f64>,
+    pub max_price: Option<f64>,
+}
+
+/// One line of the history store: the queue's bid statistics at `time`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PriceSnapshot {
+    /// Unix time the queue was read
+    pub time: i64,
+    pub buckets: Vec<BucketStats>,
+}
+
+impl PriceSnapshot {
+    fn bucket(&self, job_type: &str, status: &str) -> Option<&BucketStats> {
+        self.buckets
+            .iter()
+            .find(|b| b.job_type == job_type && b.status == status)
+    }
+}
+
+pub fn append_snapshot(
+    path: &Path,
+    snapshot: &PriceSnapshot,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(snapshot)?)?;
+    Ok(())
+}
+
+/// Snapshots taken at or after `since`, oldest first, and the number of
+/// unreadable lines (e.g. from an interrupted write) that were skipped.
+pub fn load_snapshots(
+    path: &Path,
+    since: i64,
+) -> Result<(Vec<PriceSnapshot>, usize), Box<dyn std::error::Error>> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok((Vec::new(), 0)),
+        Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e).into()),
+    };
+    let mut skipped = 0;
+    let mut snapshots: Vec<PriceSnapshot> = contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| match serde_json::from_str::<PriceSnapshot>(line) {
+            Ok(snapshot) => Some(snapshot),
+            Err(_) => {
+                skipped += 1;
+                None
+            }
+        })
+        .filter(|s| s.time >= since)
+        .collect();
+    snapshots.sort_by_key(|s| s.time);
+    Ok((snapshots, skipped))
+}
+
+/// One character per value, scaled between the smallest and largest; gaps are blank.
+fn spark(values: &[Option<f64>]) -> String {
+    let present = values.iter().flatten();
+    let lo = present.clone().copied().fold(f64::INFINITY, f64::min);
+    let hi = present.copied().fold(f64::NEG_INFINITY, f64::max);
+    values
+        .iter()
+        .map(|v| match v {
+            None => ' ',
+            Some(_) if hi <= lo => SPARK_CHARS[SPARK_CHARS.len() / 2],
+            Some(v) => {
+                let level = ((v - lo) / (hi - lo) * (SPARK_CHARS.len() - 1) as f64).round();
+                SPARK_CHARS[level as usize]
+            }
+        })
+        .collect()
+}
+
+/// Average the `(time, value)` points into `width` equal time slices from the
+/// first to the last point.
+fn resample(points: &[(i64, f64)], width: usize) -> Vec<Option<f64>> {
+    let (Some(first), Some(last)) = (points.first(), points.last()) else {
+        return Vec::new();
+    };
+    let span = (last.0 - first.0 + 1) as f64;
+    let width = width.min(points.len());
+    let mut sums = vec![(0.0, 0usize); width];
+    for (t, v) in points {
+        let slot = (((t - first.0) as f64 / span) * width as f64) as usize;
+        let slot = &mut sums[slot.min(width - 1)];
+        slot.0 += v;
+        slot.1 += 1;
+    }
+    sums.into_iter()
+        .map(|(sum, n)| (n > 0).then(|| sum / n as f64))
+        .collect()
+}
+
+/// Average of the values recorded in each hour of the day (UTC).
+fn by_hour_of_day(points: &[(i64, f64)]) -> Vec<Option<f64>> {
+    let mut sums = [(0.0, 0usize); 24];
+    for (t, v) in points {
+        let hour = (t.rem_euclid(86400) / 3600) as usize;
+        sums[hour].0 += v;
+        sums[hour].1 += 1;
+    }
+    sums.iter()
+        .map(|&(sum, n)| (n > 0).then(|| sum / n as f64))
+        .collect()
+}
+
+/// Time series of one statistic of a bucket, skipping snapshots where it was empty.
+fn series(
+    snapshots: &[PriceSnapshot],
+    job_type: &str,
+    status: &str,
+    stat: fn(&BucketStats) -> Option<f64>,
+) -> Vec<(i64, f64)> {
+    snapshots
+        .iter()
+        .filter_map(|s| Some((s.time, stat(s.bucket(job_type, status)?)?)))
+        .collect()
+}
+
+// "312 (250-400)": the latest value and the range over the period
+fn latest_and_range(points: &[(i64, f64)]) -> String {
+    let Some((_, last)) = points.last() else {
+        return "-".to_string();
+    };
+    let lo = points.iter().map(|p| p.1).fold(f64::INFINITY, f64::min);
+    let hi = points.iter().map(|p| p.1).fold(f64::NEG_INFINITY, f64::max);
+    if lo == hi {
+        return format_price(*last);
+    }
+    format!(
+        "{} ({}-{})",
+        format_price(*last),
+        format_price(lo),
+        format_price(hi)
+    )
+}
+
+fn label_cells(job_type: &str, status: &str) -> [Cell; 2] {
+    let (label, color) = match job_type {
+        "gpu" => ("GPU", Color::Green),
+        _ => ("CPU", Color::Blue),
+    };
+    let status = match status {
+        "idle" => "Idle",
+        "running" => "Running",
+        _ => "Total",
+    };
+    [Cell::new(label).fg(color), Cell::new(status)]
+}
+
+fn bold(text: &str) -> Cell {
+    Cell::new(text).add_attribute(Attribute::Bold)
+}
+
+fn create_trend_table(snapshots: &[PriceSnapshot]) -> Table {
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            bold("Job Type"),
+            bold("Status"),
+            bold("Jobs"),
+            bold("Average Price"),
+            bold("Median"),
+            bold("P90"),
+            bold("Median Trend"),
+        ]);
+    for (job_type, status) in TREND_BUCKETS {
+        let counts = series(snapshots, job_type, status, |b| Some(b.count as f64));
+        let avg = series(snapshots, job_type, status, |b| b.avg_price);
+        let median = series(snapshots, job_type, status, |b| b.median_price);
+        let p90 = series(snapshots, job_type, status, |b| b.p90_price);
+        let mut row = label_cells(job_type, status).to_vec();
+        row.extend([
+            Cell::new(latest_and_range(&counts)),
+            Cell::new(latest_and_range(&avg)),
+            Cell::new(latest_and_range(&median)),
+            Cell::new(latest_and_range(&p90)),
+            Cell::new(spark(&resample(&median, TREND_WIDTH))),
+        ]);
+        table.add_row(row);
+    }
+    table
+}
+
+// Hour of the lowest (or highest) average, as "03:00"
+fn extreme_hour(hours: &[Option<f64>], lowest: bool) -> String {
+    let best = hours
+        .iter()
+        .enumerate()
+        .filter_map(|(h, v)| v.map(|v| (h, v)))
+        .reduce(|a, b| {
+            if (b.1 < a.1) == lowest && b.1 != a.1 {
+                b
+            } else {
+                a
+            }
+        });
+    match best {
+        Some((hour, value)) => format!("{:02}:00 ({})", hour, format_price(value)),
+        None => "-".to_string(),
+    }
+}
+
+fn create_hourly_table(snapshots: &[PriceSnapshot]) -> Table {
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            bold("Job Type"),
+            bold("Status"),
+            bold("Median by Hour (00-23 UTC)"),
+            bold("Cheapest"),
+            bold("Priciest"),
+        ]);
+    for (job_type, status) in TREND_BUCKETS {
+        let hours = by_hour_of_day(&series(snapshots, job_type, status, |b| b.median_price));
+        let mut row = label_cells(job_type, status).to_vec();
+        row.extend([
+            Cell::new(spark(&hours)),
+            Cell::new(extreme_hour(&hours, true)),
+            Cell::new(extreme_hour(&hours, false)),
+        ]);
+        table.add_row(row);
+    }
+    table
+}
+
+/// One row per snapshot and bucket, including the totals.
+fn trend_records(snapshots: &[PriceSnapshot]) -> Records {
+    let mut records = Records::new(vec![
+        "time",
+        "job_type",
+        "status",
+        "count",
+        "avg_price",
+        "min_price",
+        "median_price",
+        "p75_price",
+        "p90_price",
+        "max_price",
+    ]);
+    for snapshot in snapshots {
+        for b in &snapshot.buckets {
+            records.push(vec![
+                json!(format_unix(Some(snapshot.time))),
+                json!(b.job_type),
+                json!(b.status),
+                json!(b.count),
+                json!(b.avg_price),
+                json!(b.min_price),
+                json!(b.median_price),
+                json!(b.p75_price),
+                json!(b.p90_price),
+                json!(b.max_price),
+            ]);
+        }
+    }
+    records
+}
+
+pub fn handle_price_trend(
+    path: &Path,
+    since_secs: u64,
+    output: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (snapshots, skipped) = load_snapshots(path, now_unix().saturating_sub(since_secs as i64))?;
+    if skipped > 0 {
+        eprintln!(
+            "Warning: skipped {} unreadable line(s) in {}",
+            skipped,
+            path.display()
+        );
+    }
+
+    if !output.is_table() {
+        print!("{}", trend_records(&snapshots).render(output));
+        return Ok(());
+    }
+
+    let (Some(first), Some(last)) = (snapshots.first(), snapshots.last()) else {
+        println!(
+            "No price snapshots recorded in the last {}. Record them with `mct price --record`, e.g. from cron.",
+            human_duration(since_secs)
+        );
+        return Ok(());
+    };
+    println!(
+        "{} snapshots from {} to {} ({}); latest value with the range over the period:",
+        snapshots.len(),
+        format_unix(Some(first.time)),
+        format_unix(Some(last.time)),
+        human_duration((last.time - first.time).max(0) as u64)
+    );
+    println!("{}", create_trend_table(&snapshots));
+
+    let hours_seen = snapshots
+        .iter()
+        .map(|s| s.time.rem_euclid(86400) / 3600)
+        .collect::<std::collections::BTreeSet<_>>()
+        .len();
+    if hours_seen > 1 {
+        println!("\nMedian bid by hour of day, averaged over the period:");
+        println!("{}", create_hourly_table(&snapshots));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(time: i64, gpu_idle_median: Option<f64>) -> PriceSnapshot {
+        PriceSnapshot {
+            time,
+            buckets: vec![BucketStats {
+                job_type: "gpu".into(),
+                status: "idle".into(),
+                count: gpu_idle_median.map_or(0, |_| 3),
+                avg_price: gpu_idle_median,
+                min_price: gpu_idle_median,
+                median_price: gpu_idle_median,
+                p75_price: gpu_idle_median,
+                p90_price: gpu_idle_median,
+                max_price: gpu_idle_median,
+            }],
+        }
+    }
+
+    #[test]
+    fn appends_and_loads_snapshots_since() {
+        let dir = std::env::temp_dir().join(format!("mct-price-trend-{}", std::process::id()));
+        let path = dir.join("history").join("default.jsonl");
+        for (time, median) in [(1000, Some(250.0)), (3000, None), (2000, Some(300.0))] {
+            append_snapshot(&path, &snapshot(time, median)).unwrap();
+        }
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        writeln!(file, "{{\"time\": 4000, \"buck").unwrap();
+
+        let (snapshots, skipped) = load_snapshots(&path, 1500).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(skipped, 1);
+        let times: Vec<i64> = snapshots.iter().map(|s| s.time).collect();
+        assert_eq!(times, vec![2000, 3000]);
+        let median = series(&snapshots, "gpu", "idle", |b| b.median_price);
+        assert_eq!(median, vec![(2000, 300.0)]);
+        assert_eq!(
+            load_snapshots(&dir.join("missing.jsonl"), 0).unwrap().0,
+            vec![]
+        );
+    }
+
+    #[test]
+    fn sparklines_scale_values_and_hours() {
+        assert_eq!(
+            spark(&[Some(100.0), None, Some(450.0), Some(800.0)]),
+            "▁ ▅█"
+        );
+        assert_eq!(spark(&[Some(5.0), Some(5.0)]), "▅▅");
+
+        let points = [(0, 100.0), (10, 300.0), (90, 200.0), (99, 400.0)];
+        assert_eq!(
+            resample(&points, 4),
+            vec![Some(200.0), None, None, Some(300.0)]
+        );
+
+        let hours = by_hour_of_day(&[
+            (3 * 3600, 200.0),
+            (86400 + 3 * 3600, 300.0),
+            (14 * 3600, 500.0),
+        ]);
+        assert_eq!(hours[3], Some(250.0));
+        assert_eq!(extreme_hour(&hours, true), "03:00 (250)");
+        assert_eq!(extreme_hour(&hours, false), "14:00 (500)");
+        assert_eq!(latest_and_range(&points), "400 (100-400)");
+    }
+}
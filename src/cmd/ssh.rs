@@ -0,0 +1,36 @@
+use crate::config::ClusterConfig;
+use crate::utils::ssh::{control_dir, control_master};
+
+pub fn handle_ssh_close(profile: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    let config = ClusterConfig::load();
+    let login = config.require_login(profile);
+
+    let output = control_master(login, "exit")?;
+    if output.status.success() {
+        println!("Closed shared SSH connection.");
+    } else {
+        println!("No shared SSH connection open.");
+    }
+    Ok(())
+}
+
+pub fn handle_ssh_status(profile: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    let config = ClusterConfig::load();
+    let login = config.require_login(profile);
+
+    if !login.multiplex {
+        println!("Connection sharing is disabled for this profile.");
+        return Ok(());
+    }
+
+    let output = control_master(login, "check")?;
+    if output.status.success() {
+        // `ssh -O check` reports "Master running (pid=...)" on stderr
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        println!("{}", stderr.trim());
+    } else {
+        println!("No shared SSH connection open.");
+    }
+    println!("Socket directory: {}", control_dir().display());
+    Ok(())
+}
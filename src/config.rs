@@ -1,19 +1,105 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::PathBuf;
 use std::process::Command;
 
+/// Environment variable used to select a profile when `--profile` is not given.
+pub const PROFILE_ENV: &str = "MCT_PROFILE";
+
+/// Name used for the profile created when migrating a single-login config.
+pub const DEFAULT_PROFILE_NAME: &str = "default";
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct LoginConfig {
     pub hostname: String,
     pub username: String,
     pub identity_file: Option<String>,
     pub ssh_config_name: Option<String>,
+    /// Reuse one authenticated SSH connection across commands (ControlMaster)
+    #[serde(default = "default_multiplex")]
+    pub multiplex: bool,
+    /// How long an idle shared connection is kept open, in `ControlPersist` syntax
+    #[serde(default)]
+    pub control_persist: Option<String>,
+}
+
+fn default_multiplex() -> bool {
+    true
+}
+
+impl LoginConfig {
+    // get username from login config or from ssh config
+    pub fn resolve_username(&self) -> Option<String> {
+        if !self.username.is_empty() {
+            return Some(self.username.clone());
+        }
+
+        // Fallback: resolve from SSH config using `ssh -G <alias>`
+        if let Some(alias) = &self.ssh_config_name {
+            if let Ok(output) = Command::new("ssh").args(["-G", alias]).output() {
+                if output.status.success() {
+                    if let Ok(text) = String::from_utf8(output.stdout) {
+                        for line in text.lines() {
+                            let line_trim = line.trim();
+                            // OpenSSH outputs lowercase keys in `ssh -G`
+                            if let Some(rest) = line_trim.strip_prefix("user ") {
+                                let value = rest.trim();
+                                if !value.is_empty() {
+                                    return Some(value.to_string());
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        None
+    }
+}
+
+/// A named set of cluster settings stored in `~/.cluster_tools`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Profile {
+    pub login: LoginConfig,
+    /// Warn in `mct ls` when a job is this close to its runtime limit (e.g. "30m")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warn_within: Option<String>,
+    /// Default `mct ls` columns, e.g. ["id", "status", "cmd", "memory"]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ls_columns: Option<Vec<String>>,
+    /// How `mct jobs` opens `condor_ssh_to_job`: "inplace", "tmux", "terminal-app"
+    /// or a terminal command containing `{cmd}`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ssh_launcher: Option<String>,
+    /// Auto-refresh interval of `mct jobs` (e.g. "30s"; "0" disables)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jobs_refresh: Option<String>,
+}
+
+impl Profile {
+    pub fn new(login: LoginConfig) -> Self {
+        Self {
+            login,
+            warn_within: None,
+            ls_columns: None,
+            ssh_launcher: None,
+            jobs_refresh: None,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Default)]
 pub struct ClusterConfig {
-    pub login: Option<LoginConfig>,
+    /// Profile used when neither `--profile` nor `MCT_PROFILE` is set.
+    #[serde(default)]
+    pub default_profile: Option<String>,
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+    /// Legacy single-login field; migrated into `profiles` on load.
+    #[serde(default, skip_serializing)]
+    login: Option<LoginConfig>,
 }
 
 impl ClusterConfig {
@@ -23,14 +109,27 @@ impl ClusterConfig {
             .join(".cluster_tools")
     }
 
+    /// JSONL store of `mct price --record` snapshots for `profile`, e.g.
+    /// `~/.config/mct/price_history/default.jsonl`.
+    pub fn price_history_path(profile: &str) -> PathBuf {
+        dirs::config_dir()
+            .or_else(dirs::home_dir)
+            .expect("Could not find config directory")
+            .join("mct")
+            .join("price_history")
+            .join(format!("{}.jsonl", profile))
+    }
+
     pub fn load() -> Self {
         let config_path = Self::config_path();
-        if config_path.exists() {
+        let mut config: Self = if config_path.exists() {
             let contents = fs::read_to_string(&config_path).expect("Failed to read config file");
             serde_json::from_str(&contents).unwrap_or_default()
         } else {
             Self::default()
-        }
+        };
+        config.migrate_legacy_login();
+        config
     }
 
     pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
@@ -41,33 +140,68 @@ impl ClusterConfig {
         Ok(())
     }
 
-    // get username from login config or from ssh config
-    pub fn get_username(&self) -> Option<String> {
-        let login = self.login.as_ref()?;
-        if !login.username.is_empty() {
-            return Some(login.username.clone());
+    // Older versions stored a single `login` object; turn it into the default profile
+    fn migrate_legacy_login(&mut self) {
+        if let Some(login) = self.login.take() {
+            self.profiles
+                .entry(DEFAULT_PROFILE_NAME.to_string())
+                .or_insert_with(|| Profile::new(login));
+            if self.default_profile.is_none() {
+                self.default_profile = Some(DEFAULT_PROFILE_NAME.to_string());
+            }
         }
+    }
 
-        // Fallback: resolve from SSH config using `ssh -G <alias>`
-        if let Some(alias) = &login.ssh_config_name {
-            if let Ok(output) = Command::new("ssh").args(["-G", alias]).output() {
-                if output.status.success() {
-                    if let Ok(text) = String::from_utf8(output.stdout) {
-                        for line in text.lines() {
-                            let line_trim = line.trim();
-                            // OpenSSH outputs lowercase keys in `ssh -G`
-                            if let Some(rest) = line_trim.strip_prefix("user ") {
-                                let value = rest.trim();
-                                if !value.is_empty() {
-                                    return Some(value.to_string());
-                                }
-                            }
-                        }
+    /// Name of the profile to use: the explicit selection, else the configured
+    /// default, else the only profile if there is exactly one.
+    pub fn profile_name(&self, selected: Option<&str>) -> Option<String> {
+        if let Some(name) = selected {
+            return Some(name.to_string());
+        }
+        if let Some(name) = &self.default_profile {
+            return Some(name.clone());
+        }
+        if self.profiles.len() == 1 {
+            return self.profiles.keys().next().cloned();
+        }
+        None
+    }
+
+    pub fn profile(&self, selected: Option<&str>) -> Option<&Profile> {
+        let name = self.profile_name(selected)?;
+        self.profiles.get(&name)
+    }
+
+    pub fn login(&self, selected: Option<&str>) -> Option<&LoginConfig> {
+        self.profile(selected).map(|p| &p.login)
+    }
+
+    /// Resolve the login for the selected profile, exiting with a hint if none is configured.
+    pub fn require_login(&self, selected: Option<&str>) -> &LoginConfig {
+        match self.login(selected) {
+            Some(l) => l,
+            None => {
+                match self.profile_name(selected) {
+                    Some(name) => eprintln!(
+                        "Error: No login configuration found for profile '{}'. Run 'mct login --profile {}' first.",
+                        name, name
+                    ),
+                    None => {
+                        eprintln!("Error: No login configuration found. Run 'mct login' first.")
                     }
                 }
+                std::process::exit(1);
             }
         }
+    }
 
-        None
+    /// Store `login` under `name`, keeping any other settings of an existing profile.
+    pub fn set_login(&mut self, name: &str, login: LoginConfig) {
+        match self.profiles.get_mut(name) {
+            Some(profile) => profile.login = login,
+            None => {
+                self.profiles.insert(name.to_string(), Profile::new(login));
+            }
+        }
     }
 }
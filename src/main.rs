@@ -3,23 +3,72 @@ mod config;
 mod utils;
 
 use clap::{Parser, Subcommand};
-use cmd::{handle_hist, handle_jobs, handle_list_jobs, handle_login, handle_logs, handle_price};
+use cmd::bid::BidRequest;
+use cmd::columns::{Column, DEFAULT_COLUMNS};
+use cmd::condor::JobStatus;
+use cmd::grep::GrepOptions;
+use cmd::list_jobs::{JobFilter, SortKey};
+use cmd::logs::LogFiles;
+use cmd::{
+    handle_bid_suggest, handle_events, handle_fetch, handle_hist, handle_jobs, handle_list_jobs,
+    handle_log_grep, handle_login, handle_logs, handle_price, handle_price_trend, handle_ssh_close,
+    handle_ssh_status,
+};
+use config::{ClusterConfig, DEFAULT_PROFILE_NAME};
+use std::path::PathBuf;
+use std::time::Duration;
+use utils::launcher::Launcher;
+use utils::output::OutputFormat;
+use utils::remote::{LocalExecutor, RemoteExecutor, SshExecutor};
+use utils::time::parse_duration;
+
+// Default margin for runtime-limit warnings in `mct ls`
+const DEFAULT_WARN_WITHIN_SECS: u64 = 30 * 60;
+// Default start-within target of `mct bid suggest`
+const DEFAULT_BID_WITHIN_SECS: u64 = 3600;
+// Default auto-refresh interval of `mct jobs`
+const DEFAULT_JOBS_REFRESH_SECS: u64 = 30;
 
 #[derive(Parser)]
 #[command(name = "cluster")]
 #[command(about = "A collection of cluster management commands")]
 struct Cli {
+    /// Cluster profile to use (defaults to the configured default profile)
+    #[arg(long, global = true, env = config::PROFILE_ENV)]
+    profile: Option<String>,
+    /// Run cluster commands on this machine instead of over SSH (e.g. on the submit node)
+    #[arg(long, global = true)]
+    local: bool,
+    /// Output format of commands that print tables (ls, hist, price, price trend, bid suggest, events)
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Table)]
+    output: OutputFormat,
     #[command(subcommand)]
     command: Commands,
 }
 
 #[derive(Subcommand)]
 enum Commands {
-    /// Configure login credentials for cluster access
-    Login,
+    /// Configure login credentials for cluster access (use --profile to name the profile)
+    Login {
+        /// Make this profile the default
+        #[arg(long = "set-default")]
+        set_default: bool,
+    },
     /// Analyze job prices on the cluster
-    Price,
-    /// Show logs/out/err for a running job (by id or latest)
+    #[command(args_conflicts_with_subcommands = true)]
+    Price {
+        /// Also append the statistics to the local price history (see `mct price trend`)
+        #[arg(long)]
+        record: bool,
+        #[command(subcommand)]
+        command: Option<PriceCommands>,
+    },
+    /// Help choosing a bid
+    Bid {
+        #[command(subcommand)]
+        command: BidCommands,
+    },
+    /// Show logs/out/err for a job (by id or latest), running or finished
     Logs {
         /// Job selector: <ClusterId>[.ProcId] or 'latest'/'l'
         selector: Option<String>,
@@ -35,35 +84,327 @@ enum Commands {
         /// Number of lines to show (default 50; if -o/-l/-e present and not set, defaults to 0). 0 = no limit
         #[arg(short = 'n', long = "lines")]
         lines: Option<i64>,
+        /// Keep streaming new output (like tail -F) until the job stops running
+        #[arg(short = 'f', long = "follow", conflicts_with = "grep")]
+        follow: bool,
+        /// Search the files for this extended regex instead of showing them; a bare ClusterId selects all its procs
+        #[arg(long)]
+        grep: Option<String>,
+        /// Case-insensitive --grep
+        #[arg(short = 'i', long = "ignore-case", requires = "grep")]
+        ignore_case: bool,
+        /// Lines of context around --grep matches
+        #[arg(short = 'C', long = "context", default_value_t = 0, requires = "grep")]
+        context: usize,
+        /// With --grep, search the last N jobs from condor_history instead of a selector
+        #[arg(long, requires = "grep", conflicts_with = "selector")]
+        last: Option<usize>,
+        /// Only consider jobs in these states, e.g. running or completed,removed
+        #[arg(long, value_delimiter = ',')]
+        status: Vec<JobStatus>,
+    },
+    /// Download a job's output, error and user log (and optionally other files) locally
+    Fetch {
+        /// Job selector: <ClusterId>[.ProcId] or 'latest'/'l'; a bare ClusterId fetches all its procs
+        selector: Option<String>,
+        /// Local directory; each job gets a <ClusterId>.<ProcId> subdirectory
+        #[arg(short = 'd', long = "dest", default_value = ".")]
+        dest: PathBuf,
+        /// Also fetch files under the job's Iwd matching this glob, e.g. 'checkpoints/*.pt' (repeatable)
+        #[arg(short = 'g', long = "glob")]
+        glob: Vec<String>,
+        /// Only consider jobs in these states, e.g. running or completed,removed
+        #[arg(long, value_delimiter = ',')]
+        status: Vec<JobStatus>,
+    },
+    /// Show a job's event timeline parsed from its HTCondor user log
+    Events {
+        /// Job selector: <ClusterId>[.ProcId] or 'latest'/'l' (default latest)
+        selector: Option<String>,
     },
     /// List and summarize jobs in a table
-    Ls,
+    Ls {
+        /// Columns to show, e.g. id,status,cmd,memory (default: the profile's ls_columns, else id,status,cmd,args,runtime,remaining,gpus,bid)
+        #[arg(long, value_enum, value_delimiter = ',')]
+        columns: Vec<Column>,
+        /// Warn about jobs this close to their runtime limit, e.g. 30m or 2h (default 30m)
+        #[arg(long = "warn-within", value_parser = parse_duration)]
+        warn_within: Option<u64>,
+        /// Only jobs in these states, e.g. idle,held (names, letters or codes)
+        #[arg(long, value_delimiter = ',')]
+        status: Vec<JobStatus>,
+        /// Only held jobs, with their full hold reasons
+        #[arg(long, conflicts_with = "status")]
+        held: bool,
+        /// Only jobs requesting GPUs
+        #[arg(long, conflicts_with = "cpu")]
+        gpu: bool,
+        /// Only jobs not requesting GPUs
+        #[arg(long)]
+        cpu: bool,
+        /// Only procs of this cluster id
+        #[arg(long)]
+        cluster: Option<i64>,
+        /// Only jobs whose executable matches this glob (file name, or full path if it contains '/')
+        #[arg(long)]
+        cmd: Option<String>,
+        /// Only jobs bidding at least this much
+        #[arg(long = "min-bid")]
+        min_bid: Option<f64>,
+        /// Only jobs bidding at most this much
+        #[arg(long = "max-bid")]
+        max_bid: Option<f64>,
+        /// Sort rows by this column (default: condor order)
+        #[arg(long, value_enum)]
+        sort: Option<SortKey>,
+        /// Reverse the row order
+        #[arg(long)]
+        reverse: bool,
+    },
     /// Get interactive job information
-    Jobs,
+    Jobs {
+        /// How 's' opens condor_ssh_to_job: inplace, tmux, terminal-app or a command with {cmd} (default: the profile's ssh_launcher, else inplace)
+        #[arg(long, value_parser = Launcher::parse)]
+        launcher: Option<Launcher>,
+        /// Auto-refresh interval, e.g. 10s or 2m; 0 disables (default: the profile's jobs_refresh, else 30s)
+        #[arg(long, value_parser = parse_duration)]
+        refresh: Option<u64>,
+    },
     /// Show historical jobs for the current user
     Hist {
         /// Limit number of historical jobs (default 10)
         #[arg(short = 'n', long = "num", default_value_t = 10)]
         num: usize,
     },
+    /// Manage the shared SSH connection to the cluster
+    Ssh {
+        #[command(subcommand)]
+        command: SshCommands,
+    },
+}
+
+#[derive(Subcommand)]
+enum PriceCommands {
+    /// Show how recorded bids evolved, from snapshots taken with `mct price --record`
+    Trend {
+        /// How far back to look, e.g. 24h or 2w
+        #[arg(long, value_parser = parse_duration, default_value = "7d")]
+        since: u64,
+    },
+}
+
+#[derive(Subcommand)]
+enum BidCommands {
+    /// Recommend a bid for a new job from the queue's prices and the matching machines
+    Suggest {
+        /// GPUs the job requests
+        #[arg(long)]
+        gpus: i64,
+        /// Memory the job requests, in MB
+        #[arg(long)]
+        memory: Option<i64>,
+        /// Minimum memory per GPU, in MB (e.g. 60000 for 80 GB cards)
+        #[arg(long = "gpu-mem")]
+        gpu_mem: Option<i64>,
+        /// How soon the job should start, e.g. 30m or 4h (default 1h)
+        #[arg(long, value_parser = parse_duration)]
+        within: Option<u64>,
+    },
+}
+
+#[derive(Subcommand)]
+enum SshCommands {
+    /// Close the shared SSH connection
+    Close,
+    /// Show whether a shared SSH connection is open
+    Status,
+}
+
+fn executor(config: &ClusterConfig, profile: Option<&str>, local: bool) -> Box<dyn RemoteExecutor> {
+    if local {
+        return Box::new(LocalExecutor);
+    }
+    Box::new(SshExecutor::new(config.require_login(profile).clone()))
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let cli = Cli::parse();
+    let profile = cli.profile.as_deref();
+    let config = ClusterConfig::load();
+    let exec = || executor(&config, profile, cli.local);
 
     match cli.command {
-        Commands::Login => handle_login()?,
-        Commands::Price => handle_price()?,
+        Commands::Login { set_default } => handle_login(profile, set_default)?,
+        Commands::Price { record, command } => {
+            let history = ClusterConfig::price_history_path(
+                &config
+                    .profile_name(profile)
+                    .unwrap_or_else(|| DEFAULT_PROFILE_NAME.to_string()),
+            );
+            match command {
+                Some(PriceCommands::Trend { since }) => {
+                    handle_price_trend(&history, since, cli.output)?
+                }
+                None => handle_price(
+                    exec().as_ref(),
+                    cli.output,
+                    record.then_some(history.as_path()),
+                )?,
+            }
+        }
+        Commands::Bid { command } => match command {
+            BidCommands::Suggest {
+                gpus,
+                memory,
+                gpu_mem,
+                within,
+            } => {
+                let request = BidRequest {
+                    gpus,
+                    memory_mb: memory,
+                    gpu_memory_mb: gpu_mem,
+                    within_secs: within.unwrap_or(DEFAULT_BID_WITHIN_SECS),
+                };
+                handle_bid_suggest(exec().as_ref(), &request, cli.output)?
+            }
+        },
         Commands::Logs {
             selector,
             out,
             log,
             err,
             lines,
-        } => handle_logs(selector, out, log, err, lines)?,
-        Commands::Ls => handle_list_jobs()?,
-        Commands::Jobs => handle_jobs()?,
-        Commands::Hist { num } => handle_hist(Some(num))?,
+            follow,
+            grep,
+            ignore_case,
+            context,
+            last,
+            status,
+        } => match grep {
+            Some(pattern) => handle_log_grep(
+                exec().as_ref(),
+                selector,
+                last,
+                LogFiles { out, log, err },
+                &status,
+                &GrepOptions {
+                    pattern,
+                    ignore_case,
+                    context,
+                },
+            )?,
+            None => handle_logs(
+                exec().as_ref(),
+                selector,
+                LogFiles { out, log, err },
+                lines,
+                follow,
+                &status,
+            )?,
+        },
+        Commands::Ls {
+            columns,
+            warn_within,
+            status,
+            held,
+            gpu,
+            cpu,
+            cluster,
+            cmd,
+            min_bid,
+            max_bid,
+            sort,
+            reverse,
+        } => {
+            let warn_within = match warn_within {
+                Some(secs) => secs,
+                None => match config
+                    .profile(profile)
+                    .and_then(|p| p.warn_within.as_deref())
+                {
+                    Some(s) => parse_duration(s)?,
+                    None => DEFAULT_WARN_WITHIN_SECS,
+                },
+            };
+            let mut columns = if !columns.is_empty() {
+                columns
+            } else {
+                match config.profile(profile).and_then(|p| p.ls_columns.as_ref()) {
+                    Some(names) => names
+                        .iter()
+                        .map(|n| Column::parse(n))
+                        .collect::<Result<Vec<_>, _>>()?,
+                    None => DEFAULT_COLUMNS.to_vec(),
+                }
+            };
+            let status = if held {
+                if !columns.contains(&Column::HoldReason) {
+                    columns.push(Column::HoldReason);
+                }
+                vec![JobStatus::Held]
+            } else {
+                status
+            };
+            let filter = JobFilter {
+                statuses: status,
+                gpu: if gpu {
+                    Some(true)
+                } else if cpu {
+                    Some(false)
+                } else {
+                    None
+                },
+                cluster,
+                cmd,
+                min_bid,
+                max_bid,
+            };
+            handle_list_jobs(
+                exec().as_ref(),
+                &columns,
+                warn_within,
+                &filter,
+                sort,
+                reverse,
+                cli.output,
+            )?
+        }
+        Commands::Fetch {
+            selector,
+            dest,
+            glob,
+            status,
+        } => handle_fetch(exec().as_ref(), selector, &dest, &glob, &status)?,
+        Commands::Events { selector } => handle_events(exec().as_ref(), selector, cli.output)?,
+        Commands::Jobs { launcher, refresh } => {
+            let launcher = match launcher {
+                Some(launcher) => launcher,
+                None => match config
+                    .profile(profile)
+                    .and_then(|p| p.ssh_launcher.as_deref())
+                {
+                    Some(s) => Launcher::parse(s)?,
+                    None => Launcher::default(),
+                },
+            };
+            let refresh = match refresh {
+                Some(secs) => secs,
+                None => match config
+                    .profile(profile)
+                    .and_then(|p| p.jobs_refresh.as_deref())
+                {
+                    Some(s) => parse_duration(s)?,
+                    None => DEFAULT_JOBS_REFRESH_SECS,
+                },
+            };
+            let refresh_every = (refresh > 0).then(|| Duration::from_secs(refresh));
+            handle_jobs(exec().as_ref(), &launcher, refresh_every)?
+        }
+        Commands::Hist { num } => handle_hist(exec().as_ref(), Some(num), cli.output)?,
+        Commands::Ssh { command } => match command {
+            SshCommands::Close => handle_ssh_close(profile)?,
+            SshCommands::Status => handle_ssh_status(profile)?,
+        },
     }
 
     Ok(())
@@ -0,0 +1,722 @@
+//! Parser and evaluator for the subset of the ClassAd expression language that
+//! shows up in job ads: literals, attribute references, arithmetic, comparisons,
+//! `is`/`isnt`, boolean logic and the `?:` conditional.
+
+use crate::utils::serde::ClassAdValue;
+
+/// Attribute lookup used while evaluating an expression.
+pub trait ClassAdEnv {
+    /// Value of attribute `name` (case-insensitive), `None` if not set.
+    fn lookup(&self, name: &str) -> Option<ClassAdValue>;
+}
+
+impl ClassAdEnv for std::collections::HashMap<String, ClassAdValue> {
+    fn lookup(&self, name: &str) -> Option<ClassAdValue> {
+        self.iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.clone())
+    }
+}
+
+/// Adds the built-in `CurrentTime` attribute on top of another environment.
+pub struct WithCurrentTime<'a> {
+    pub env: &'a dyn ClassAdEnv,
+    pub now: i64,
+}
+
+impl ClassAdEnv for WithCurrentTime<'_> {
+    fn lookup(&self, name: &str) -> Option<ClassAdValue> {
+        if name.eq_ignore_ascii_case("CurrentTime") {
+            return Some(ClassAdValue::Int(self.now));
+        }
+        self.env.lookup(name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnaryOp {
+    Neg,
+    Not,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+    /// `is` / `=?=`: identical type and value, never undefined
+    Is,
+    /// `isnt` / `=!=`
+    Isnt,
+    And,
+    Or,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Literal(ClassAdValue),
+    /// Attribute reference; `target` is set for `TARGET.` references, which
+    /// refer to the matched machine ad and evaluate to undefined here.
+    Attr {
+        name: String,
+        target: bool,
+    },
+    Unary(UnaryOp, Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Cond(Box<Expr>, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Int(i64),
+    Real(f64),
+    Str(String),
+    Ident(String),
+    Op(&'static str),
+}
+
+const OPERATORS: [&str; 22] = [
+    "=?=", "=!=", "<=", ">=", "==", "!=", "&&", "||", "(", ")", "!", "-", "+", "*", "/", "%", "<",
+    ">", "?", ":", ",", ".",
+];
+
+fn tokenize(src: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = src.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() {
+            let start = i;
+            let mut is_real = false;
+            while i < chars.len() {
+                let d = chars[i];
+                if d.is_ascii_digit() {
+                    i += 1;
+                } else if d == '.' && !is_real {
+                    is_real = true;
+                    i += 1;
+                } else if (d == 'e' || d == 'E')
+                    && chars
+                        .get(i + 1)
+                        .is_some_and(|n| n.is_ascii_digit() || *n == '-' || *n == '+')
+                {
+                    is_real = true;
+                    i += 2;
+                } else {
+                    break;
+                }
+            }
+            let text: String = chars[start..i].iter().collect();
+            if is_real {
+                let v = text
+                    .parse()
+                    .map_err(|_| format!("invalid number '{}'", text))?;
+                tokens.push(Token::Real(v));
+            } else {
+                let v = text
+                    .parse()
+                    .map_err(|_| format!("invalid number '{}'", text))?;
+                tokens.push(Token::Int(v));
+            }
+        } else if c == '"' {
+            let mut s = String::new();
+            i += 1;
+            loop {
+                match chars.get(i) {
+                    None => return Err("unterminated string literal".to_string()),
+                    Some('"') => {
+                        i += 1;
+                        break;
+                    }
+                    Some('\\') => {
+                        if let Some(n) = chars.get(i + 1) {
+                            s.push(*n);
+                        }
+                        i += 2;
+                    }
+                    Some(ch) => {
+                        s.push(*ch);
+                        i += 1;
+                    }
+                }
+            }
+            tokens.push(Token::Str(s));
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(Token::Ident(chars[start..i].iter().collect()));
+        } else {
+            let rest: String = chars[i..chars.len().min(i + 3)].iter().collect();
+            let op = OPERATORS
+                .iter()
+                .find(|op| rest.starts_with(*op))
+                .ok_or_else(|| format!("unexpected character '{}'", c))?;
+            tokens.push(Token::Op(op));
+            i += op.len();
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let t = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        t
+    }
+
+    fn eat_op(&mut self, op: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Op(o)) if *o == op) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_keyword(&mut self, kw: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Ident(i)) if i.eq_ignore_ascii_case(kw)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect_op(&mut self, op: &str) -> Result<(), String> {
+        if self.eat_op(op) {
+            Ok(())
+        } else {
+            Err(format!("expected '{}'", op))
+        }
+    }
+
+    fn conditional(&mut self) -> Result<Expr, String> {
+        let cond = self.or()?;
+        if self.eat_op("?") {
+            let then = self.conditional()?;
+            self.expect_op(":")?;
+            let otherwise = self.conditional()?;
+            return Ok(Expr::Cond(
+                Box::new(cond),
+                Box::new(then),
+                Box::new(otherwise),
+            ));
+        }
+        Ok(cond)
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.and()?;
+        while self.eat_op("||") {
+            let rhs = self.and()?;
+            lhs = Expr::Binary(BinaryOp::Or, Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.equality()?;
+        while self.eat_op("&&") {
+            let rhs = self.equality()?;
+            lhs = Expr::Binary(BinaryOp::And, Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn equality(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.relational()?;
+        loop {
+            let op = if self.eat_op("==") {
+                BinaryOp::Eq
+            } else if self.eat_op("!=") {
+                BinaryOp::Ne
+            } else if self.eat_op("=?=") || self.eat_keyword("is") {
+                BinaryOp::Is
+            } else if self.eat_op("=!=") || self.eat_keyword("isnt") {
+                BinaryOp::Isnt
+            } else {
+                return Ok(lhs);
+            };
+            let rhs = self.relational()?;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs));
+        }
+    }
+
+    fn relational(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.additive()?;
+        loop {
+            let op = if self.eat_op("<=") {
+                BinaryOp::Le
+            } else if self.eat_op(">=") {
+                BinaryOp::Ge
+            } else if self.eat_op("<") {
+                BinaryOp::Lt
+            } else if self.eat_op(">") {
+                BinaryOp::Gt
+            } else {
+                return Ok(lhs);
+            };
+            let rhs = self.additive()?;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs));
+        }
+    }
+
+    fn additive(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.multiplicative()?;
+        loop {
+            let op = if self.eat_op("+") {
+                BinaryOp::Add
+            } else if self.eat_op("-") {
+                BinaryOp::Sub
+            } else {
+                return Ok(lhs);
+            };
+            let rhs = self.multiplicative()?;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs));
+        }
+    }
+
+    fn multiplicative(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.unary()?;
+        loop {
+            let op = if self.eat_op("*") {
+                BinaryOp::Mul
+            } else if self.eat_op("/") {
+                BinaryOp::Div
+            } else if self.eat_op("%") {
+                BinaryOp::Mod
+            } else {
+                return Ok(lhs);
+            };
+            let rhs = self.unary()?;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.eat_op("-") {
+            return Ok(Expr::Unary(UnaryOp::Neg, Box::new(self.unary()?)));
+        }
+        if self.eat_op("!") {
+            return Ok(Expr::Unary(UnaryOp::Not, Box::new(self.unary()?)));
+        }
+        if self.eat_op("+") {
+            return self.unary();
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Some(Token::Int(v)) => Ok(Expr::Literal(ClassAdValue::Int(v))),
+            Some(Token::Real(v)) => Ok(Expr::Literal(ClassAdValue::Real(v))),
+            Some(Token::Str(s)) => Ok(Expr::Literal(ClassAdValue::Str(s))),
+            Some(Token::Op("(")) => {
+                let e = self.conditional()?;
+                self.expect_op(")")?;
+                Ok(e)
+            }
+            Some(Token::Ident(id)) => {
+                match id.to_ascii_lowercase().as_str() {
+                    "true" => return Ok(Expr::Literal(ClassAdValue::Bool(true))),
+                    "false" => return Ok(Expr::Literal(ClassAdValue::Bool(false))),
+                    "undefined" => return Ok(Expr::Literal(ClassAdValue::Undefined)),
+                    "error" => return Ok(Expr::Literal(ClassAdValue::Error)),
+                    _ => {}
+                }
+                if self.eat_op(".") {
+                    let scope = id.to_ascii_lowercase();
+                    let name = match self.next() {
+                        Some(Token::Ident(name)) => name,
+                        _ => return Err(format!("expected attribute name after '{}.'", id)),
+                    };
+                    return match scope.as_str() {
+                        "my" => Ok(Expr::Attr {
+                            name,
+                            target: false,
+                        }),
+                        "target" => Ok(Expr::Attr { name, target: true }),
+                        _ => Err(format!("unsupported scope '{}'", id)),
+                    };
+                }
+                if matches!(self.peek(), Some(Token::Op("("))) {
+                    return Err(format!("function calls are not supported ('{}')", id));
+                }
+                Ok(Expr::Attr {
+                    name: id,
+                    target: false,
+                })
+            }
+            Some(t) => Err(format!("unexpected token {:?}", t)),
+            None => Err("unexpected end of expression".to_string()),
+        }
+    }
+}
+
+/// Parse a ClassAd expression.
+pub fn parse(src: &str) -> Result<Expr, String> {
+    let mut parser = Parser {
+        tokens: tokenize(src)?,
+        pos: 0,
+    };
+    let expr = parser.conditional()?;
+    if parser.pos < parser.tokens.len() {
+        return Err(format!(
+            "unexpected trailing input {:?}",
+            parser.tokens[parser.pos]
+        ));
+    }
+    Ok(expr)
+}
+
+fn arithmetic(op: BinaryOp, a: &ClassAdValue, b: &ClassAdValue) -> ClassAdValue {
+    use ClassAdValue::*;
+    match (a, b) {
+        (Error, _) | (_, Error) => Error,
+        (Undefined, _) | (_, Undefined) => Undefined,
+        (Int(x), Int(y)) => match op {
+            BinaryOp::Add => Int(x.wrapping_add(*y)),
+            BinaryOp::Sub => Int(x.wrapping_sub(*y)),
+            BinaryOp::Mul => Int(x.wrapping_mul(*y)),
+            BinaryOp::Div if *y == 0 => Error,
+            BinaryOp::Div => Int(x.wrapping_div(*y)),
+            BinaryOp::Mod if *y == 0 => Error,
+            BinaryOp::Mod => Int(x.wrapping_rem(*y)),
+            _ => Error,
+        },
+        _ => match (a.as_f64(), b.as_f64()) {
+            (Some(x), Some(y)) => match op {
+                BinaryOp::Add => Real(x + y),
+                BinaryOp::Sub => Real(x - y),
+                BinaryOp::Mul => Real(x * y),
+                BinaryOp::Div if y == 0.0 => Error,
+                BinaryOp::Div => Real(x / y),
+                BinaryOp::Mod if y == 0.0 => Error,
+                BinaryOp::Mod => Real(x % y),
+                _ => Error,
+            },
+            _ => Error,
+        },
+    }
+}
+
+fn compare(op: BinaryOp, a: &ClassAdValue, b: &ClassAdValue) -> ClassAdValue {
+    use ClassAdValue::*;
+    let ordering = match (a, b) {
+        (Error, _) | (_, Error) => return Error,
+        (Undefined, _) | (_, Undefined) => return Undefined,
+        // String comparison with == / < is case-insensitive in ClassAds
+        (Str(x), Str(y)) => x.to_lowercase().cmp(&y.to_lowercase()),
+        _ => match (a.as_f64(), b.as_f64()) {
+            (Some(x), Some(y)) => match x.partial_cmp(&y) {
+                Some(o) => o,
+                None => return Error,
+            },
+            _ => return Error,
+        },
+    };
+    let result = match op {
+        BinaryOp::Lt => ordering.is_lt(),
+        BinaryOp::Le => ordering.is_le(),
+        BinaryOp::Gt => ordering.is_gt(),
+        BinaryOp::Ge => ordering.is_ge(),
+        BinaryOp::Eq => ordering.is_eq(),
+        BinaryOp::Ne => ordering.is_ne(),
+        _ => return Error,
+    };
+    Bool(result)
+}
+
+fn identical(a: &ClassAdValue, b: &ClassAdValue) -> bool {
+    use ClassAdValue::*;
+    match (a, b) {
+        (Int(x), Int(y)) => x == y,
+        (Real(x), Real(y)) => x == y,
+        (Bool(x), Bool(y)) => x == y,
+        (Str(x), Str(y)) => x == y,
+        (Undefined, Undefined) | (Error, Error) => true,
+        _ => false,
+    }
+}
+
+// Bound on nested attribute expansion so self-referencing attributes can't recurse forever
+const MAX_DEPTH: usize = 32;
+
+impl Expr {
+    pub fn eval(&self, env: &dyn ClassAdEnv) -> ClassAdValue {
+        self.eval_at(env, 0)
+    }
+
+    fn eval_at(&self, env: &dyn ClassAdEnv, depth: usize) -> ClassAdValue {
+        let eval = |e: &Expr| e.eval_at(env, depth);
+        match self {
+            Expr::Literal(v) => v.clone(),
+            Expr::Attr { target: true, .. } => ClassAdValue::Undefined,
+            Expr::Attr { name, .. } => match env.lookup(name) {
+                Some(ClassAdValue::Expr(_)) if depth >= MAX_DEPTH => ClassAdValue::Error,
+                Some(ClassAdValue::Expr(src)) => match parse(&src) {
+                    Ok(e) => e.eval_at(env, depth + 1),
+                    Err(_) => ClassAdValue::Error,
+                },
+                Some(v) => v,
+                None => ClassAdValue::Undefined,
+            },
+            Expr::Unary(op, e) => match (op, eval(e)) {
+                (_, ClassAdValue::Undefined) => ClassAdValue::Undefined,
+                (UnaryOp::Neg, ClassAdValue::Int(v)) => ClassAdValue::Int(v.wrapping_neg()),
+                (UnaryOp::Neg, ClassAdValue::Real(v)) => ClassAdValue::Real(-v),
+                (UnaryOp::Not, ClassAdValue::Bool(v)) => ClassAdValue::Bool(!v),
+                _ => ClassAdValue::Error,
+            },
+            Expr::Binary(BinaryOp::And, a, b) => match eval(a) {
+                ClassAdValue::Bool(false) => ClassAdValue::Bool(false),
+                ClassAdValue::Bool(true) => match eval(b) {
+                    v @ (ClassAdValue::Bool(_) | ClassAdValue::Undefined) => v,
+                    _ => ClassAdValue::Error,
+                },
+                ClassAdValue::Undefined => match eval(b) {
+                    ClassAdValue::Bool(false) => ClassAdValue::Bool(false),
+                    ClassAdValue::Bool(true) | ClassAdValue::Undefined => ClassAdValue::Undefined,
+                    _ => ClassAdValue::Error,
+                },
+                _ => ClassAdValue::Error,
+            },
+            Expr::Binary(BinaryOp::Or, a, b) => match eval(a) {
+                ClassAdValue::Bool(true) => ClassAdValue::Bool(true),
+                ClassAdValue::Bool(false) => match eval(b) {
+                    v @ (ClassAdValue::Bool(_) | ClassAdValue::Undefined) => v,
+                    _ => ClassAdValue::Error,
+                },
+                ClassAdValue::Undefined => match eval(b) {
+                    ClassAdValue::Bool(true) => ClassAdValue::Bool(true),
+                    ClassAdValue::Bool(false) | ClassAdValue::Undefined => ClassAdValue::Undefined,
+                    _ => ClassAdValue::Error,
+                },
+                _ => ClassAdValue::Error,
+            },
+            Expr::Binary(op, a, b) => {
+                let (a, b) = (eval(a), eval(b));
+                match op {
+                    BinaryOp::Add
+                    | BinaryOp::Sub
+                    | BinaryOp::Mul
+                    | BinaryOp::Div
+                    | BinaryOp::Mod => arithmetic(*op, &a, &b),
+                    BinaryOp::Is => ClassAdValue::Bool(identical(&a, &b)),
+                    BinaryOp::Isnt => ClassAdValue::Bool(!identical(&a, &b)),
+                    _ => compare(*op, &a, &b),
+                }
+            }
+            Expr::Cond(c, then, otherwise) => match eval(c).as_bool() {
+                Some(true) => eval(then),
+                Some(false) => eval(otherwise),
+                None => ClassAdValue::Undefined,
+            },
+        }
+    }
+}
+
+/// Earliest time in `[from, from + horizon]` at which `expr` evaluates to true
+/// with `CurrentTime` set to that time. Assumes the expression stays true once it
+/// becomes true, as wall-clock limits like `CurrentTime - JobCurrentStartDate > N` do.
+pub fn first_true_time(expr: &Expr, env: &dyn ClassAdEnv, from: i64, horizon: i64) -> Option<i64> {
+    let holds_at = |now: i64| {
+        let scope = WithCurrentTime { env, now };
+        expr.eval(&scope) == ClassAdValue::Bool(true)
+    };
+    let (mut lo, mut hi) = (from, from + horizon);
+    if !holds_at(hi) {
+        return None;
+    }
+    if holds_at(lo) {
+        return Some(lo);
+    }
+    // Invariant: false at lo, true at hi
+    while hi - lo > 1 {
+        let mid = lo + (hi - lo) / 2;
+        if holds_at(mid) {
+            hi = mid;
+        } else {
+            lo = mid;
+        }
+    }
+    Some(hi)
+}
+
+/// Largest lower bound that the top-level `&&` terms of `expr` place on attribute
+/// `name` (case-insensitive, with or without `TARGET.`), e.g. 60000 for
+/// `(TARGET.CUDAGlobalMemoryMb > 60000) && (TARGET.Arch == "X86_64")`.
+pub fn lower_bound(expr: &Expr, name: &str) -> Option<f64> {
+    let Expr::Binary(op, a, b) = expr else {
+        return None;
+    };
+    let is_attr =
+        |e: &Expr| matches!(e, Expr::Attr { name: n, .. } if n.eq_ignore_ascii_case(name));
+    match (op, a.as_ref(), b.as_ref()) {
+        (BinaryOp::And, a, b) => match (lower_bound(a, name), lower_bound(b, name)) {
+            (Some(x), Some(y)) => Some(x.max(y)),
+            (x, y) => x.or(y),
+        },
+        (BinaryOp::Gt | BinaryOp::Ge, attr, Expr::Literal(v))
+        | (BinaryOp::Lt | BinaryOp::Le, Expr::Literal(v), attr)
+            if is_attr(attr) =>
+        {
+            v.as_f64()
+        }
+        _ => None,
+    }
+}
+
+impl ClassAdValue {
+    /// Evaluate this value against `env`; literals evaluate to themselves.
+    pub fn eval(&self, env: &dyn ClassAdEnv) -> ClassAdValue {
+        match self {
+            ClassAdValue::Expr(src) => match parse(src) {
+                Ok(e) => e.eval(env),
+                Err(_) => ClassAdValue::Error,
+            },
+            v => v.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn env(pairs: &[(&str, ClassAdValue)]) -> HashMap<String, ClassAdValue> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn evaluates_arithmetic_and_comparison() {
+        let e = env(&[]);
+        assert_eq!(parse("1 + 2 * 3").unwrap().eval(&e), ClassAdValue::Int(7));
+        assert_eq!(parse("(1 + 2) * 3").unwrap().eval(&e), ClassAdValue::Int(9));
+        assert_eq!(parse("7 / 2.0").unwrap().eval(&e), ClassAdValue::Real(3.5));
+        assert_eq!(parse("1 / 0").unwrap().eval(&e), ClassAdValue::Error);
+        // Overflow wraps instead of panicking
+        let min = env(&[("Min", ClassAdValue::Int(i64::MIN))]);
+        for (src, expected) in [("Min / -1", i64::MIN), ("Min % -1", 0), ("-Min", i64::MIN)] {
+            assert_eq!(parse(src).unwrap().eval(&min), ClassAdValue::Int(expected));
+        }
+        assert_eq!(
+            parse("\"LINUX\" == \"linux\"").unwrap().eval(&e),
+            ClassAdValue::Bool(true)
+        );
+        assert_eq!(
+            parse("\"LINUX\" is \"linux\"").unwrap().eval(&e),
+            ClassAdValue::Bool(false)
+        );
+        assert_eq!(
+            parse("x > 1 ? 10 : 20").unwrap().eval(&e),
+            ClassAdValue::Undefined
+        );
+    }
+
+    #[test]
+    fn undefined_propagates_through_logic() {
+        let e = env(&[]);
+        assert_eq!(
+            parse("Missing > 3 && false").unwrap().eval(&e),
+            ClassAdValue::Bool(false)
+        );
+        assert_eq!(
+            parse("Missing > 3 || true").unwrap().eval(&e),
+            ClassAdValue::Bool(true)
+        );
+        assert_eq!(
+            parse("Missing is undefined").unwrap().eval(&e),
+            ClassAdValue::Bool(true)
+        );
+        assert_eq!(
+            parse("TARGET.Memory >= 10").unwrap().eval(&e),
+            ClassAdValue::Undefined
+        );
+    }
+
+    #[test]
+    fn resolves_nested_expressions_and_current_time() {
+        let ad = env(&[
+            ("DiskUsage", ClassAdValue::Int(42)),
+            ("RequestDisk", ClassAdValue::Expr("DiskUsage".into())),
+            ("JobStatus", ClassAdValue::Int(2)),
+            ("JobCurrentStartDate", ClassAdValue::Int(1000)),
+        ]);
+        let scope = WithCurrentTime {
+            env: &ad,
+            now: 17_000,
+        };
+        assert_eq!(
+            ClassAdValue::Expr("MY.RequestDisk * 2".into()).eval(&scope),
+            ClassAdValue::Int(84)
+        );
+        let remove = ClassAdValue::from_json_str(
+            "/Expr((JobStatus is 2) && ((CurrentTime - JobCurrentStartDate) >= 16000))/",
+        );
+        assert_eq!(remove.eval(&scope), ClassAdValue::Bool(true));
+    }
+
+    #[test]
+    fn finds_when_wall_clock_limit_is_reached() {
+        let ad = env(&[
+            ("JobStatus", ClassAdValue::Int(2)),
+            ("JobCurrentStartDate", ClassAdValue::Int(1000)),
+        ]);
+        let expr =
+            parse("(JobStatus is 2) && ((CurrentTime - JobCurrentStartDate) >= 16000)").unwrap();
+        assert_eq!(first_true_time(&expr, &ad, 5000, 86400), Some(17000));
+        assert_eq!(first_true_time(&expr, &ad, 20000, 86400), Some(20000));
+
+        let idle = env(&[("JobStatus", ClassAdValue::Int(1))]);
+        assert_eq!(first_true_time(&expr, &idle, 5000, 86400), None);
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        assert!(parse("1 +").is_err());
+        assert!(parse("(1").is_err());
+        assert!(parse("\"open").is_err());
+        assert!(parse("strcmp(a, b)").is_err());
+    }
+
+    #[test]
+    fn finds_lower_bounds_in_conjunctions() {
+        let expr = parse(
+            "(TARGET.CUDAGlobalMemoryMb > 60000) && (TARGET.Arch == \"X86_64\") && (40000 <= cudaglobalmemorymb)",
+        )
+        .unwrap();
+        assert_eq!(lower_bound(&expr, "CUDAGlobalMemoryMb"), Some(60000.0));
+        assert_eq!(lower_bound(&expr, "Memory"), None);
+        // Alternatives do not guarantee a bound
+        let either = parse("TARGET.CUDAGlobalMemoryMb > 60000 || TARGET.GPUs > 1").unwrap();
+        assert_eq!(lower_bound(&either, "CUDAGlobalMemoryMb"), None);
+    }
+}
@@ -0,0 +1,190 @@
+//! Opening an interactive command (e.g. `condor_ssh_to_job`) from the jobs TUI.
+
+use crate::utils::ssh::shell_escape_single_quotes;
+use crossterm::{cursor, execute, terminal};
+use std::io::{stdout, Read};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// Placeholder for the shell-quoted command in a terminal template.
+pub const CMD_PLACEHOLDER: &str = "{cmd}";
+/// Placeholder for a window title (the job id) in a terminal template.
+pub const TITLE_PLACEHOLDER: &str = "{title}";
+
+// How long a template command may fail before it counts as launched
+const TEMPLATE_STARTUP: Duration = Duration::from_millis(500);
+
+/// Where the jobs TUI runs interactive commands.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Launcher {
+    /// Suspend the TUI and run in the current terminal, returning afterwards
+    #[default]
+    InPlace,
+    /// New window in the enclosing tmux session
+    Tmux,
+    /// New macOS Terminal.app window via osascript
+    TerminalApp,
+    /// Shell command with `{cmd}` (and optionally `{title}`), e.g. `alacritty -e sh -c {cmd}`
+    Template(String),
+}
+
+impl Launcher {
+    pub fn parse(value: &str) -> Result<Launcher, String> {
+        let value = value.trim();
+        match value.to_ascii_lowercase().as_str() {
+            "inplace" | "in-place" | "suspend" => Ok(Launcher::InPlace),
+            "tmux" => Ok(Launcher::Tmux),
+            "terminal-app" | "macos" => Ok(Launcher::TerminalApp),
+            _ if value.contains(CMD_PLACEHOLDER) => Ok(Launcher::Template(value.to_string())),
+            _ => Err(format!(
+                "unknown launcher '{}' (expected inplace, tmux, terminal-app or a command containing {})",
+                value, CMD_PLACEHOLDER
+            )),
+        }
+    }
+
+    /// Run `argv`, labelled `title`. Returns a message for the status line.
+    pub fn launch(&self, argv: &[String], title: &str) -> Result<String, String> {
+        let shell_cmd = shell_join(argv);
+        match self {
+            Launcher::InPlace => run_in_place(argv, title),
+            Launcher::Tmux => {
+                if std::env::var_os("TMUX").is_none() {
+                    return Err("tmux launcher: not running inside a tmux session".into());
+                }
+                let out = Command::new("tmux")
+                    .args(["new-window", "-n", title, &shell_cmd])
+                    .output()
+                    .map_err(|e| format!("Failed to run tmux: {}", e))?;
+                if !out.status.success() {
+                    return Err(format!(
+                        "tmux new-window failed: {}",
+                        String::from_utf8_lossy(&out.stderr).trim()
+                    ));
+                }
+                Ok(format!("Opened {} in a new tmux window", title))
+            }
+            Launcher::TerminalApp => {
+                let script_cmd = shell_cmd.replace('\\', "\\\\").replace('"', "\\\"");
+                let osa = format!(
+                    "tell application \"Terminal\" to do script \"{}\"",
+                    script_cmd
+                );
+                let out = Command::new("osascript")
+                    .arg("-e")
+                    .arg(osa)
+                    .output()
+                    .map_err(|e| format!("Failed to run osascript: {}", e))?;
+                if !out.status.success() {
+                    return Err(format!(
+                        "osascript failed: {}",
+                        String::from_utf8_lossy(&out.stderr).trim()
+                    ));
+                }
+                Ok(format!("Opened {} in Terminal.app", title))
+            }
+            Launcher::Template(template) => {
+                spawn_template(&expand_template(template, &shell_cmd, title))?;
+                Ok(format!("Opened {} in a new terminal", title))
+            }
+        }
+    }
+}
+
+fn shell_join(argv: &[String]) -> String {
+    argv.iter()
+        .map(|a| format!("'{}'", shell_escape_single_quotes(a)))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Substitute the placeholders with single shell words.
+fn expand_template(template: &str, shell_cmd: &str, title: &str) -> String {
+    template
+        .replace(
+            CMD_PLACEHOLDER,
+            &format!("'{}'", shell_escape_single_quotes(shell_cmd)),
+        )
+        .replace(
+            TITLE_PLACEHOLDER,
+            &format!("'{}'", shell_escape_single_quotes(title)),
+        )
+}
+
+// Terminal emulators may keep running until their window closes, so only an
+// early failure is reported
+fn spawn_template(cmd: &str) -> Result<(), String> {
+    let mut child = Command::new("sh")
+        .args(["-c", cmd])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to start '{}': {}", cmd, e))?;
+    let started = Instant::now();
+    while started.elapsed() < TEMPLATE_STARTUP {
+        if let Some(status) = child.try_wait().map_err(|e| e.to_string())? {
+            if status.success() {
+                return Ok(());
+            }
+            let mut stderr = String::new();
+            if let Some(mut pipe) = child.stderr.take() {
+                let _ = pipe.read_to_string(&mut stderr);
+            }
+            return Err(format!(
+                "Terminal command failed ({}): {}",
+                status,
+                stderr.trim()
+            ));
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    Ok(())
+}
+
+// Leave the TUI screen, run attached to the terminal, then restore the TUI
+fn run_in_place(argv: &[String], title: &str) -> Result<String, String> {
+    let mut out = stdout();
+    let _ = execute!(out, terminal::LeaveAlternateScreen, cursor::Show);
+    let _ = terminal::disable_raw_mode();
+    println!(
+        "Connecting to {} (exit the shell to return to the job list)...",
+        title
+    );
+    let status = Command::new(&argv[0]).args(&argv[1..]).status();
+    let _ = terminal::enable_raw_mode();
+    let _ = execute!(out, terminal::EnterAlternateScreen, cursor::Hide);
+    match status {
+        Ok(s) if s.success() => Ok(format!("Session on {} ended", title)),
+        Ok(s) => Err(format!("Session on {} ended with {}", title, s)),
+        Err(e) => Err(format!("Failed to run {}: {}", argv[0], e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_named_launchers_and_templates() {
+        assert_eq!(Launcher::parse("In-Place"), Ok(Launcher::InPlace));
+        assert_eq!(Launcher::parse("tmux"), Ok(Launcher::Tmux));
+        assert_eq!(
+            Launcher::parse("kitty --title {title} sh -c {cmd}"),
+            Ok(Launcher::Template(
+                "kitty --title {title} sh -c {cmd}".into()
+            ))
+        );
+        assert!(Launcher::parse("xterm").is_err());
+    }
+
+    #[test]
+    fn template_receives_command_as_one_word() {
+        let shell_cmd = shell_join(&["ssh".into(), "condor_ssh_to_job 1.0".into()]);
+        assert_eq!(shell_cmd, "'ssh' 'condor_ssh_to_job 1.0'");
+        assert_eq!(
+            expand_template("xterm -T {title} -e sh -c {cmd}", &shell_cmd, "1.0"),
+            "xterm -T '1.0' -e sh -c ''\\''ssh'\\'' '\\''condor_ssh_to_job 1.0'\\'''"
+        );
+    }
+}
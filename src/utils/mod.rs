@@ -1,2 +1,8 @@
+pub mod classad;
+pub mod launcher;
+pub mod output;
+pub mod remote;
 pub mod serde;
 pub mod ssh;
+pub mod time;
+pub mod userlog;
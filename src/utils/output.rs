@@ -0,0 +1,184 @@
+use clap::ValueEnum;
+use serde_json::Value;
+
+/// How command results are printed.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// Human-readable table
+    #[default]
+    Table,
+    /// JSON array of objects
+    Json,
+    /// Comma-separated values with a header row
+    Csv,
+    /// Tab-separated values with a header row
+    Tsv,
+    /// Whitespace-aligned columns without borders
+    Plain,
+}
+
+impl OutputFormat {
+    pub fn is_table(&self) -> bool {
+        *self == OutputFormat::Table
+    }
+}
+
+/// Rows of raw values under stable column names, for the machine-readable formats.
+pub struct Records {
+    columns: Vec<&'static str>,
+    rows: Vec<Vec<Value>>,
+}
+
+impl Records {
+    pub fn new(columns: Vec<&'static str>) -> Self {
+        Self {
+            columns,
+            rows: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, row: Vec<Value>) {
+        debug_assert_eq!(row.len(), self.columns.len());
+        self.rows.push(row);
+    }
+
+    /// Render in `format`; `Table` is handled by each command and falls back to `Plain` here.
+    pub fn render(&self, format: OutputFormat) -> String {
+        match format {
+            OutputFormat::Json => self.to_json(),
+            OutputFormat::Csv => self.to_delimited(',', csv_field),
+            OutputFormat::Tsv => self.to_delimited('\t', tsv_field),
+            OutputFormat::Table | OutputFormat::Plain => self.to_plain(),
+        }
+    }
+
+    fn to_json(&self) -> String {
+        let objects: Vec<Value> = self
+            .rows
+            .iter()
+            .map(|row| {
+                let map = self
+                    .columns
+                    .iter()
+                    .zip(row)
+                    .map(|(c, v)| (c.to_string(), v.clone()))
+                    .collect();
+                Value::Object(map)
+            })
+            .collect();
+        // Newline-terminated like the other formats
+        serde_json::to_string_pretty(&objects).unwrap_or_else(|_| "[]".to_string()) + "\n"
+    }
+
+    fn to_delimited(&self, sep: char, field: fn(&str) -> String) -> String {
+        let mut out = String::new();
+        let header: Vec<String> = self.columns.iter().map(|c| field(c)).collect();
+        out.push_str(&header.join(&sep.to_string()));
+        out.push('\n');
+        for row in &self.rows {
+            let cells: Vec<String> = row.iter().map(|v| field(&raw_text(v))).collect();
+            out.push_str(&cells.join(&sep.to_string()));
+            out.push('\n');
+        }
+        out
+    }
+
+    fn to_plain(&self) -> String {
+        let cells: Vec<Vec<String>> = self
+            .rows
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|v| match raw_text(v) {
+                        s if s.is_empty() => "-".to_string(),
+                        s => s.replace(['\n', '\t'], " "),
+                    })
+                    .collect()
+            })
+            .collect();
+        let widths: Vec<usize> = self
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(i, c)| {
+                cells
+                    .iter()
+                    .map(|r| r[i].chars().count())
+                    .chain(std::iter::once(c.len()))
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+        let line = |values: Vec<&str>| -> String {
+            let padded: Vec<String> = values
+                .iter()
+                .zip(&widths)
+                .map(|(v, w)| format!("{:<width$}", v, width = *w))
+                .collect();
+            padded.join("  ").trim_end().to_string() + "\n"
+        };
+        let mut out = line(self.columns.clone());
+        for row in &cells {
+            out.push_str(&line(row.iter().map(|s| s.as_str()).collect()));
+        }
+        out
+    }
+}
+
+fn raw_text(v: &Value) -> String {
+    match v {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+fn tsv_field(s: &str) -> String {
+    s.replace(['\t', '\n', '\r'], " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn sample() -> Records {
+        let mut r = Records::new(vec!["job_id", "args", "gpus"]);
+        r.push(vec![json!("1.0"), json!("a,b \"c\""), json!(2)]);
+        r.push(vec![json!("2.0"), Value::Null, json!(0)]);
+        r
+    }
+
+    #[test]
+    fn renders_csv_with_quoting() {
+        assert_eq!(
+            sample().render(OutputFormat::Csv),
+            "job_id,args,gpus\n1.0,\"a,b \"\"c\"\"\",2\n2.0,,0\n"
+        );
+    }
+
+    #[test]
+    fn renders_json_objects() {
+        let json = sample().render(OutputFormat::Json);
+        assert!(json.ends_with("]\n"));
+        let v: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(v[0]["gpus"], 2);
+        assert_eq!(v[1]["args"], Value::Null);
+    }
+
+    #[test]
+    fn renders_plain_columns() {
+        assert_eq!(
+            sample().render(OutputFormat::Plain),
+            "job_id  args     gpus\n1.0     a,b \"c\"  2\n2.0     -        0\n"
+        );
+    }
+}
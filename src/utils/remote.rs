@@ -0,0 +1,443 @@
+use crate::config::LoginConfig;
+use crate::utils::ssh::{
+    remote_command, run_remote, shell_escape_single_quotes, ssh_destination_args, ssh_host,
+    ssh_option_args,
+};
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Exit status and captured output of a command run by a [`RemoteExecutor`].
+#[derive(Debug, Clone, Default)]
+pub struct CommandOutput {
+    /// Exit code, `None` if the process was terminated by a signal
+    pub code: Option<i32>,
+    pub stdout: Vec<u8>,
+    pub stderr: Vec<u8>,
+}
+
+impl CommandOutput {
+    pub fn success(&self) -> bool {
+        self.code == Some(0)
+    }
+}
+
+impl From<std::process::Output> for CommandOutput {
+    fn from(output: std::process::Output) -> Self {
+        Self {
+            code: output.status.code(),
+            stdout: output.stdout,
+            stderr: output.stderr,
+        }
+    }
+}
+
+/// Runs shell commands on the cluster's submit node.
+pub trait RemoteExecutor: Send + Sync {
+    /// Run `cmd` through the remote shell and capture its output.
+    fn run(&self, cmd: &str) -> Result<CommandOutput, Box<dyn std::error::Error>>;
+
+    /// Run `cmd` and pass each stdout line to `on_line` as it arrives, stopping
+    /// the command early once `on_line` returns `false`. Returns the exit code.
+    fn stream(
+        &self,
+        cmd: &str,
+        on_line: &mut dyn FnMut(&str) -> bool,
+    ) -> Result<Option<i32>, Box<dyn std::error::Error>>;
+
+    /// User whose jobs are queried.
+    fn username(&self) -> Option<String>;
+
+    /// Command line that runs `cmd` with the current terminal attached, if supported.
+    fn interactive_argv(&self, cmd: &str) -> Option<Vec<String>>;
+
+    /// Copy `sources` from the submit node into the local directory `dest`.
+    /// With `relative`, a source written as `dir/./sub/file` is stored as `dest/sub/file`.
+    fn download(
+        &self,
+        sources: &[String],
+        dest: &Path,
+        relative: bool,
+    ) -> Result<CommandOutput, Box<dyn std::error::Error>>;
+}
+
+fn has_rsync() -> bool {
+    Command::new("rsync")
+        .arg("--version")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|s| s.success())
+}
+
+/// Command line copying `sources` to `dest`, from `host` over ssh with `ssh_opts`
+/// if `remote` is given. rsync keeps partially copied files and skips files whose
+/// size and mtime are unchanged, so repeated fetches only copy what changed and
+/// interrupted ones resume; scp and cp always copy whole files.
+fn transfer_argv(
+    remote: Option<(&[String], &str)>,
+    sources: &[String],
+    dest: &Path,
+    relative: bool,
+    rsync: bool,
+) -> Vec<String> {
+    let source = |s: &String| match remote {
+        Some((_, host)) => format!("{}:{}", host, s),
+        None => s.clone(),
+    };
+    let mut argv: Vec<String> = Vec::new();
+    if rsync {
+        argv.extend(
+            ["rsync", "-a", "-s", "--partial"]
+                .iter()
+                .map(|a| a.to_string()),
+        );
+        if relative {
+            argv.push("-R".to_string());
+        }
+        if let Some((opts, _)) = remote {
+            let shell = std::iter::once("ssh".to_string())
+                .chain(opts.iter().map(|o| {
+                    if o.contains(char::is_whitespace) {
+                        format!("'{}'", shell_escape_single_quotes(o))
+                    } else {
+                        o.clone()
+                    }
+                }))
+                .collect::<Vec<_>>()
+                .join(" ");
+            argv.push("-e".to_string());
+            argv.push(shell);
+        }
+    } else if let Some((opts, _)) = remote {
+        argv.push("scp".to_string());
+        argv.push("-p".to_string());
+        argv.extend(opts.iter().cloned());
+    } else {
+        argv.push("cp".to_string());
+        argv.push("-p".to_string());
+    }
+    argv.extend(sources.iter().map(source));
+    argv.push(format!("{}/", dest.display()));
+    argv
+}
+
+fn run_argv(argv: &[String]) -> Result<CommandOutput, Box<dyn std::error::Error>> {
+    let output = Command::new(&argv[0])
+        .args(&argv[1..])
+        .output()
+        .map_err(|e| format!("Failed to execute {}: {}", argv[0], e))?;
+    Ok(output.into())
+}
+
+/// Run the transfer with rsync if available. scp and cp cannot recreate the
+/// part after `/./`, so relative sources are copied one target directory at a time.
+fn transfer(
+    remote: Option<(&[String], &str)>,
+    sources: &[String],
+    dest: &Path,
+    relative: bool,
+) -> Result<CommandOutput, Box<dyn std::error::Error>> {
+    let rsync = has_rsync();
+    if !relative || rsync {
+        return run_argv(&transfer_argv(remote, sources, dest, relative, rsync));
+    }
+    let mut groups: Vec<(std::path::PathBuf, Vec<String>)> = Vec::new();
+    for source in sources {
+        let rel = source.split_once("/./").map(|(_, r)| r).unwrap_or(source);
+        let dir = dest.join(Path::new(rel).parent().unwrap_or(Path::new("")));
+        match groups.iter_mut().find(|(d, _)| *d == dir) {
+            Some((_, group)) => group.push(source.clone()),
+            None => groups.push((dir, vec![source.clone()])),
+        }
+    }
+    let mut combined = CommandOutput {
+        code: Some(0),
+        ..Default::default()
+    };
+    for (dir, group) in groups {
+        std::fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        let out = run_argv(&transfer_argv(remote, &group, &dir, false, false))?;
+        if !out.success() {
+            combined.code = out.code;
+        }
+        combined.stdout.extend(out.stdout);
+        combined.stderr.extend(out.stderr);
+    }
+    Ok(combined)
+}
+
+// Shared by the executors that spawn a real process
+fn stream_command(
+    mut command: Command,
+    on_line: &mut dyn FnMut(&str) -> bool,
+) -> Result<Option<i32>, Box<dyn std::error::Error>> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to execute command: {}", e))?;
+    let stdout = child
+        .stdout
+        .take()
+        .ok_or("Failed to capture command output")?;
+    for line in BufReader::new(stdout).lines() {
+        if !on_line(&line?) {
+            let _ = child.kill();
+            break;
+        }
+    }
+    Ok(child.wait()?.code())
+}
+
+pub fn require_username(exec: &dyn RemoteExecutor) -> Result<String, std::io::Error> {
+    exec.username()
+        .ok_or_else(|| std::io::Error::other("No username found in login or ssh config"))
+}
+
+/// Executes commands over SSH using a profile's login settings.
+pub struct SshExecutor {
+    login: LoginConfig,
+}
+
+impl SshExecutor {
+    pub fn new(login: LoginConfig) -> Self {
+        Self { login }
+    }
+}
+
+impl RemoteExecutor for SshExecutor {
+    fn run(&self, cmd: &str) -> Result<CommandOutput, Box<dyn std::error::Error>> {
+        Ok(run_remote(&self.login, cmd)?.into())
+    }
+
+    fn stream(
+        &self,
+        cmd: &str,
+        on_line: &mut dyn FnMut(&str) -> bool,
+    ) -> Result<Option<i32>, Box<dyn std::error::Error>> {
+        stream_command(remote_command(&self.login, cmd), on_line)
+    }
+
+    fn username(&self) -> Option<String> {
+        self.login.resolve_username()
+    }
+
+    fn interactive_argv(&self, cmd: &str) -> Option<Vec<String>> {
+        let mut args = vec!["ssh".to_string(), "-t".to_string()];
+        args.extend(ssh_destination_args(&self.login));
+        args.push(cmd.to_string());
+        Some(args)
+    }
+
+    fn download(
+        &self,
+        sources: &[String],
+        dest: &Path,
+        relative: bool,
+    ) -> Result<CommandOutput, Box<dyn std::error::Error>> {
+        let opts = ssh_option_args(&self.login);
+        let host = ssh_host(&self.login);
+        transfer(Some((&opts, &host)), sources, dest, relative)
+    }
+}
+
+/// Executes commands on the local machine, for use directly on a submit node.
+pub struct LocalExecutor;
+
+impl RemoteExecutor for LocalExecutor {
+    fn run(&self, cmd: &str) -> Result<CommandOutput, Box<dyn std::error::Error>> {
+        let output = Command::new("sh")
+            .args(["-c", cmd])
+            .output()
+            .map_err(|e| format!("Failed to execute command: {}", e))?;
+        Ok(output.into())
+    }
+
+    fn stream(
+        &self,
+        cmd: &str,
+        on_line: &mut dyn FnMut(&str) -> bool,
+    ) -> Result<Option<i32>, Box<dyn std::error::Error>> {
+        let mut command = Command::new("sh");
+        command.args(["-c", cmd]);
+        stream_command(command, on_line)
+    }
+
+    fn username(&self) -> Option<String> {
+        std::env::var("USER")
+            .or_else(|_| std::env::var("LOGNAME"))
+            .ok()
+            .filter(|u| !u.is_empty())
+    }
+
+    fn interactive_argv(&self, cmd: &str) -> Option<Vec<String>> {
+        Some(vec!["sh".to_string(), "-c".to_string(), cmd.to_string()])
+    }
+
+    fn download(
+        &self,
+        sources: &[String],
+        dest: &Path,
+        relative: bool,
+    ) -> Result<CommandOutput, Box<dyn std::error::Error>> {
+        transfer(None, sources, dest, relative)
+    }
+}
+
+/// Replays canned outputs keyed by the exact command string.
+#[cfg(test)]
+pub struct FixtureExecutor {
+    username: String,
+    responses: std::collections::HashMap<String, CommandOutput>,
+    calls: std::sync::Mutex<Vec<String>>,
+}
+
+#[cfg(test)]
+impl FixtureExecutor {
+    pub fn new(username: &str) -> Self {
+        Self {
+            username: username.to_string(),
+            responses: std::collections::HashMap::new(),
+            calls: std::sync::Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Answer `cmd` with a successful exit and the given stdout.
+    pub fn with_stdout(self, cmd: &str, stdout: impl Into<Vec<u8>>) -> Self {
+        self.with_output(
+            cmd,
+            CommandOutput {
+                code: Some(0),
+                stdout: stdout.into(),
+                stderr: Vec::new(),
+            },
+        )
+    }
+
+    pub fn with_output(mut self, cmd: &str, output: CommandOutput) -> Self {
+        self.responses.insert(cmd.to_string(), output);
+        self
+    }
+
+    /// Commands run so far, in order.
+    pub fn calls(&self) -> Vec<String> {
+        self.calls.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+impl RemoteExecutor for FixtureExecutor {
+    fn run(&self, cmd: &str) -> Result<CommandOutput, Box<dyn std::error::Error>> {
+        self.calls.lock().unwrap().push(cmd.to_string());
+        self.responses
+            .get(cmd)
+            .cloned()
+            .ok_or_else(|| format!("No fixture recorded for command: {}", cmd).into())
+    }
+
+    fn stream(
+        &self,
+        cmd: &str,
+        on_line: &mut dyn FnMut(&str) -> bool,
+    ) -> Result<Option<i32>, Box<dyn std::error::Error>> {
+        let output = self.run(cmd)?;
+        for line in String::from_utf8_lossy(&output.stdout).lines() {
+            if !on_line(line) {
+                break;
+            }
+        }
+        Ok(output.code)
+    }
+
+    fn username(&self) -> Option<String> {
+        Some(self.username.clone())
+    }
+
+    fn interactive_argv(&self, _cmd: &str) -> Option<Vec<String>> {
+        None
+    }
+
+    /// Recorded as a `download <sources> -> <dest>` call; nothing is copied.
+    fn download(
+        &self,
+        sources: &[String],
+        dest: &Path,
+        relative: bool,
+    ) -> Result<CommandOutput, Box<dyn std::error::Error>> {
+        self.calls.lock().unwrap().push(format!(
+            "download{} {} -> {}",
+            if relative { " -R" } else { "" },
+            sources.join(" "),
+            dest.display()
+        ));
+        Ok(CommandOutput {
+            code: Some(0),
+            ..Default::default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rsync_reuses_ssh_options_and_resumes() {
+        let opts = vec!["-o".to_string(), "ControlPath=/run/my dir/%C".to_string()];
+        let argv = transfer_argv(
+            Some((&opts, "euler")),
+            &["/scratch/./ckpt/last.pt".to_string()],
+            Path::new("out/1.0"),
+            true,
+            true,
+        );
+        assert_eq!(
+            argv,
+            vec![
+                "rsync",
+                "-a",
+                "-s",
+                "--partial",
+                "-R",
+                "-e",
+                "ssh -o 'ControlPath=/run/my dir/%C'",
+                "euler:/scratch/./ckpt/last.pt",
+                "out/1.0/"
+            ]
+        );
+        // Files rewritten at the same size (checkpoints, restarted logs) must be
+        // copied again, so rsync decides by size and mtime rather than appending
+        assert_eq!(
+            transfer_argv(
+                None,
+                &["/scratch/run.log".to_string()],
+                Path::new("out"),
+                false,
+                true
+            ),
+            vec!["rsync", "-a", "-s", "--partial", "/scratch/run.log", "out/"]
+        );
+    }
+
+    #[test]
+    fn falls_back_to_scp_and_cp() {
+        let opts = vec!["-i".to_string(), "~/.ssh/id".to_string()];
+        let sources = ["/a.out".to_string()];
+        assert_eq!(
+            transfer_argv(
+                Some((&opts, "me@host")),
+                &sources,
+                Path::new("d"),
+                false,
+                false
+            ),
+            vec!["scp", "-p", "-i", "~/.ssh/id", "me@host:/a.out", "d/"]
+        );
+        assert_eq!(
+            transfer_argv(None, &sources, Path::new("d"), false, false),
+            vec!["cp", "-p", "/a.out", "d/"]
+        );
+    }
+}
@@ -1,82 +1,69 @@
 use serde::Deserializer;
 
-pub fn deserialize_request_gpus<'de, D>(deserializer: D) -> Result<i32, D::Error>
+/// Integer attribute that may also be a number of another type, null, or an
+/// unevaluated expression string (which yields `None`).
+pub fn deserialize_opt_i64_lenient<'de, D>(deserializer: D) -> Result<Option<i64>, D::Error>
 where
     D: Deserializer<'de>,
 {
     use serde::de::{self, Visitor};
     use std::fmt;
 
-    struct RequestGPUsVisitor;
-
-    impl<'de> Visitor<'de> for RequestGPUsVisitor {
-        type Value = i32;
-
-        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-            formatter.write_str("an integer or string representing GPU count")
-        }
-
-        fn visit_i8<E>(self, value: i8) -> Result<Self::Value, E>
-        where
-            E: de::Error,
-        {
-            Ok(value as i32)
+    struct V;
+    impl<'de> Visitor<'de> for V {
+        type Value = Option<i64>;
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("an integer, string or null")
         }
-
-        fn visit_i16<E>(self, value: i16) -> Result<Self::Value, E>
+        fn visit_bool<E>(self, v: bool) -> Result<Self::Value, E>
         where
             E: de::Error,
         {
-            Ok(value as i32)
+            Ok(Some(v as i64))
         }
-
-        fn visit_i32<E>(self, value: i32) -> Result<Self::Value, E>
+        fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
         where
             E: de::Error,
         {
-            Ok(value)
+            Ok(Some(v))
         }
-
-        fn visit_i64<E>(self, value: i64) -> Result<Self::Value, E>
+        fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
         where
             E: de::Error,
         {
-            Ok(value as i32)
+            Ok(Some(v as i64))
         }
-
-        fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E>
+        fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E>
         where
             E: de::Error,
         {
-            Ok(value as i32)
+            Ok(Some(v as i64))
         }
-
-        fn visit_f64<E>(self, value: f64) -> Result<Self::Value, E>
+        fn visit_str<E>(self, s: &str) -> Result<Self::Value, E>
         where
             E: de::Error,
         {
-            Ok(value as i32)
+            Ok(s.trim().parse().ok())
         }
-
-        fn visit_str<E>(self, _value: &str) -> Result<Self::Value, E>
+        fn visit_none<E>(self) -> Result<Self::Value, E>
         where
             E: de::Error,
         {
-            Ok(0)
+            Ok(None)
         }
-
-        fn visit_string<E>(self, _value: String) -> Result<Self::Value, E>
+        fn visit_unit<E>(self) -> Result<Self::Value, E>
         where
             E: de::Error,
         {
-            Ok(0)
+            Ok(None)
         }
     }
 
-    deserializer.deserialize_any(RequestGPUsVisitor)
+    deserializer.deserialize_any(V)
 }
 
-pub fn deserialize_i64_lenient<'de, D>(deserializer: D) -> Result<i64, D::Error>
+/// Floating point counterpart of [`deserialize_opt_i64_lenient`].
+pub fn deserialize_opt_f64_lenient<'de, D>(deserializer: D) -> Result<Option<f64>, D::Error>
 where
     D: Deserializer<'de>,
 {
@@ -85,41 +72,177 @@ where
 
     struct V;
     impl<'de> Visitor<'de> for V {
-        type Value = i64;
+        type Value = Option<f64>;
         fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
-            f.write_str("an integer or string")
+            f.write_str("a number, string or null")
         }
         fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
         where
             E: de::Error,
         {
-            Ok(v)
+            Ok(Some(v as f64))
         }
         fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
         where
             E: de::Error,
         {
-            Ok(v as i64)
+            Ok(Some(v as f64))
         }
         fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E>
         where
             E: de::Error,
         {
-            Ok(v as i64)
+            Ok(Some(v))
+        }
+        fn visit_str<E>(self, s: &str) -> Result<Self::Value, E>
+        where
+            E: de::Error,
+        {
+            Ok(s.trim().parse().ok())
         }
-        fn visit_str<E>(self, _s: &str) -> Result<Self::Value, E>
+        fn visit_none<E>(self) -> Result<Self::Value, E>
         where
             E: de::Error,
         {
-            Ok(0)
+            Ok(None)
         }
-        fn visit_string<E>(self, _s: String) -> Result<Self::Value, E>
+        fn visit_unit<E>(self) -> Result<Self::Value, E>
         where
             E: de::Error,
         {
-            Ok(0)
+            Ok(None)
         }
     }
 
     deserializer.deserialize_any(V)
 }
+
+/// A ClassAd attribute value as emitted by `condor_q -json`: either a literal
+/// or an unevaluated expression, which HTCondor serialises as `"/Expr(...)/"`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClassAdValue {
+    Undefined,
+    Error,
+    Bool(bool),
+    Int(i64),
+    Real(f64),
+    Str(String),
+    /// Expression source without the `/Expr(` `)/` wrapper
+    Expr(String),
+}
+
+impl ClassAdValue {
+    /// Classify a JSON string, unwrapping `/Expr(...)/` expressions.
+    pub fn from_json_str(s: &str) -> Self {
+        match s
+            .strip_prefix("/Expr(")
+            .and_then(|rest| rest.strip_suffix(")/"))
+        {
+            Some(expr) => ClassAdValue::Expr(expr.to_string()),
+            None => ClassAdValue::Str(s.to_string()),
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            ClassAdValue::Int(v) => Some(*v as f64),
+            ClassAdValue::Real(v) => Some(*v),
+            ClassAdValue::Bool(v) => Some(*v as i64 as f64),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            ClassAdValue::Int(v) => Some(*v),
+            ClassAdValue::Real(v) => Some(*v as i64),
+            ClassAdValue::Bool(v) => Some(*v as i64),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            ClassAdValue::Bool(v) => Some(*v),
+            ClassAdValue::Int(v) => Some(*v != 0),
+            ClassAdValue::Real(v) => Some(*v != 0.0),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for ClassAdValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClassAdValue::Undefined => f.write_str("undefined"),
+            ClassAdValue::Error => f.write_str("error"),
+            ClassAdValue::Bool(v) => write!(f, "{}", v),
+            ClassAdValue::Int(v) => write!(f, "{}", v),
+            ClassAdValue::Real(v) => write!(f, "{}", v),
+            ClassAdValue::Str(v) => f.write_str(v),
+            ClassAdValue::Expr(v) => f.write_str(v),
+        }
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for ClassAdValue {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        use serde::de::{self, Visitor};
+        use std::fmt;
+
+        struct V;
+        impl<'de> Visitor<'de> for V {
+            type Value = ClassAdValue;
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a ClassAd literal or /Expr(...)/ string")
+            }
+            fn visit_bool<E>(self, v: bool) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(ClassAdValue::Bool(v))
+            }
+            fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(ClassAdValue::Int(v))
+            }
+            fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(ClassAdValue::Int(v as i64))
+            }
+            fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(ClassAdValue::Real(v))
+            }
+            fn visit_str<E>(self, s: &str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(ClassAdValue::from_json_str(s))
+            }
+            fn visit_none<E>(self) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(ClassAdValue::Undefined)
+            }
+            fn visit_unit<E>(self) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(ClassAdValue::Undefined)
+            }
+        }
+
+        deserializer.deserialize_any(V)
+    }
+}
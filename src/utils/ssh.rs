@@ -1,14 +1,85 @@
 use crate::config::LoginConfig;
 use serde::de::DeserializeOwned;
+use std::fs;
+use std::path::PathBuf;
 use std::process::Command;
 
-pub fn ssh_base_args(login: &LoginConfig) -> Vec<String> {
-    let mut args: Vec<String> = vec![
-        "ssh".to_string(),
-        "-T".to_string(),
+const DEFAULT_CONTROL_PERSIST: &str = "10m";
+
+/// Directory holding the ControlMaster sockets of shared SSH connections.
+pub fn control_dir() -> PathBuf {
+    match dirs::runtime_dir() {
+        Some(dir) => dir.join("mct"),
+        None => {
+            let user = std::env::var("USER").unwrap_or_else(|_| "user".to_string());
+            std::env::temp_dir().join(format!("mct-{}", user))
+        }
+    }
+}
+
+fn ensure_control_dir() -> std::io::Result<PathBuf> {
+    let dir = control_dir();
+    fs::create_dir_all(&dir)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o700))?;
+    }
+    Ok(dir)
+}
+
+/// `ControlPath` option shared by the master connection and `ssh -O` control commands.
+/// `%C` lets ssh derive a unique socket name per host/user/port.
+pub fn control_path_arg() -> String {
+    format!("ControlPath={}/%C", control_dir().display())
+}
+
+fn multiplex_args(login: &LoginConfig) -> Vec<String> {
+    // ControlMaster relies on unix domain sockets
+    if !login.multiplex || !cfg!(unix) {
+        return Vec::new();
+    }
+    // Fall back to a plain connection if the socket directory can't be prepared
+    if ensure_control_dir().is_err() {
+        return Vec::new();
+    }
+    let persist = login
+        .control_persist
+        .as_deref()
+        .unwrap_or(DEFAULT_CONTROL_PERSIST);
+    vec![
         "-o".to_string(),
-        "LogLevel=ERROR".to_string(),
-    ];
+        "ControlMaster=auto".to_string(),
+        "-o".to_string(),
+        control_path_arg(),
+        "-o".to_string(),
+        format!("ControlPersist={}", persist),
+    ]
+}
+
+/// Host argument for scp/rsync sources: the ssh config alias or `user@host`.
+pub fn ssh_host(login: &LoginConfig) -> String {
+    match &login.ssh_config_name {
+        Some(name) => name.clone(),
+        None => format!("{}@{}", login.username, login.hostname),
+    }
+}
+
+/// The options of [`ssh_base_args`] without the destination, for scp and rsync.
+pub fn ssh_option_args(login: &LoginConfig) -> Vec<String> {
+    let mut args = vec!["-o".to_string(), "LogLevel=ERROR".to_string()];
+    args.extend(multiplex_args(login));
+    if login.ssh_config_name.is_none() {
+        if let Some(identity) = &login.identity_file {
+            args.push("-i".to_string());
+            args.push(identity.clone());
+        }
+    }
+    args
+}
+
+pub fn ssh_destination_args(login: &LoginConfig) -> Vec<String> {
+    let mut args: Vec<String> = Vec::new();
     if let Some(name) = &login.ssh_config_name {
         args.push(name.clone());
     } else {
@@ -21,6 +92,48 @@ pub fn ssh_base_args(login: &LoginConfig) -> Vec<String> {
     args
 }
 
+pub fn ssh_base_args(login: &LoginConfig) -> Vec<String> {
+    let mut args: Vec<String> = vec![
+        "ssh".to_string(),
+        "-T".to_string(),
+        "-o".to_string(),
+        "LogLevel=ERROR".to_string(),
+    ];
+    args.extend(multiplex_args(login));
+    args.extend(ssh_destination_args(login));
+    args
+}
+
+/// Send a control command (`check`, `exit`, ...) to the shared connection of `login`.
+pub fn control_master(
+    login: &LoginConfig,
+    ctl_cmd: &str,
+) -> Result<std::process::Output, Box<dyn std::error::Error>> {
+    let mut args: Vec<String> = vec![
+        "-o".to_string(),
+        "LogLevel=ERROR".to_string(),
+        "-o".to_string(),
+        control_path_arg(),
+        "-O".to_string(),
+        ctl_cmd.to_string(),
+    ];
+    args.extend(ssh_destination_args(login));
+    let output = Command::new("ssh")
+        .args(&args)
+        .output()
+        .map_err(|e| format!("Failed to execute SSH command: {}", e))?;
+    Ok(output)
+}
+
+/// `ssh` command that runs `remote_cmd`, for callers that need to spawn it themselves.
+pub fn remote_command(login: &LoginConfig, remote_cmd: &str) -> Command {
+    let mut args = ssh_base_args(login);
+    args.push(remote_cmd.to_string());
+    let mut command = Command::new(&args[0]);
+    command.args(&args[1..]);
+    command
+}
+
 pub fn run_remote(
     login: &LoginConfig,
     remote_cmd: &str,
@@ -0,0 +1,72 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub fn now_unix() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or(Duration::from_secs(0))
+        .as_secs() as i64
+}
+
+/// Compact human-readable duration such as `3h 05m`, `4m 10s` or `12s`.
+pub fn human_duration(secs: u64) -> String {
+    let h = secs / 3600;
+    let m = (secs % 3600) / 60;
+    let s = secs % 60;
+    if h > 0 {
+        format!("{}h {:02}m", h, m)
+    } else if m > 0 {
+        format!("{}m {:02}s", m, s)
+    } else {
+        format!("{}s", s)
+    }
+}
+
+/// Parse a duration like `90s`, `30m`, `2h`, `7d` or `1w`; a bare number is minutes.
+pub fn parse_duration(input: &str) -> Result<u64, String> {
+    let s = input.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (num, unit) = s.split_at(split);
+    let value: u64 = num
+        .parse()
+        .map_err(|_| format!("Invalid duration '{}'", input))?;
+    let factor = match unit.trim() {
+        "s" => 1,
+        "" | "m" => 60,
+        "h" => 3600,
+        "d" => 86400,
+        "w" => 7 * 86400,
+        _ => return Err(format!("Invalid duration unit in '{}'", input)),
+    };
+    let secs = value
+        .checked_mul(factor)
+        .ok_or_else(|| format!("Duration '{}' is too large", input))?;
+    // Callers add durations to Unix times and Instants, which are signed
+    Ok(secs.min(i64::MAX as u64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("90s"), Ok(90));
+        assert_eq!(parse_duration("30"), Ok(1800));
+        assert_eq!(parse_duration("2h"), Ok(7200));
+        assert_eq!(parse_duration("7d"), Ok(604800));
+        assert!(parse_duration("soon").is_err());
+        assert!(parse_duration("3y").is_err());
+        assert_eq!(
+            parse_duration("99999999999999999w"),
+            Err("Duration '99999999999999999w' is too large".to_string())
+        );
+        assert_eq!(parse_duration("18446744073709551615s"), Ok(i64::MAX as u64));
+    }
+
+    #[test]
+    fn formats_durations() {
+        assert_eq!(human_duration(12), "12s");
+        assert_eq!(human_duration(250), "4m 10s");
+        assert_eq!(human_duration(3 * 3600 + 300), "3h 05m");
+    }
+}
@@ -0,0 +1,343 @@
+//! Parser for the classic (text) HTCondor job event log written to `UserLog`.
+//!
+//! Each event starts with a header line such as
+//! `005 (16337546.000.000) 2025-08-08 19:30:01 Job terminated.`, is followed by
+//! indented detail lines and ends with a line containing only `...`.
+
+use time::{Date, Month, OffsetDateTime, PrimitiveDateTime, Time};
+
+/// How a job's process ended, from a `005 Job terminated` event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Termination {
+    /// Exited normally with this return value
+    Exit(i64),
+    /// Killed by this signal
+    Signal(i64),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EventKind {
+    Submit,
+    Execute {
+        host: Option<String>,
+    },
+    ExecutableError,
+    Evicted,
+    Terminated(Option<Termination>),
+    ImageSize {
+        image_kb: Option<i64>,
+        memory_mb: Option<i64>,
+        rss_kb: Option<i64>,
+    },
+    Aborted {
+        reason: Option<String>,
+    },
+    Suspended,
+    Unsuspended,
+    Held {
+        reason: Option<String>,
+        code: Option<i64>,
+        subcode: Option<i64>,
+    },
+    Released {
+        reason: Option<String>,
+    },
+    /// Input/output sandbox transfer (event 040), e.g. "Started transferring input files"
+    FileTransfer {
+        description: String,
+    },
+    /// Any other event, identified by its number
+    Other(u32),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UserLogEvent {
+    pub code: u32,
+    pub cluster_id: i64,
+    pub proc_id: i64,
+    /// Timestamp as written in the log (submit node's local time)
+    pub time: PrimitiveDateTime,
+    /// Rest of the header line, e.g. "Job terminated."
+    pub title: String,
+    /// Detail lines with indentation removed
+    pub details: Vec<String>,
+    pub kind: EventKind,
+}
+
+impl UserLogEvent {
+    /// Seconds on a naive clock, for differences between events of the same log.
+    pub fn timestamp(&self) -> i64 {
+        self.time.assume_utc().unix_timestamp()
+    }
+
+    /// `YYYY-MM-DD HH:MM:SS`
+    pub fn display_time(&self) -> String {
+        format!(
+            "{} {:02}:{:02}:{:02}",
+            self.time.date(),
+            self.time.hour(),
+            self.time.minute(),
+            self.time.second()
+        )
+    }
+}
+
+/// Parse all events in `text`, skipping anything that does not look like an event.
+pub fn parse_user_log(text: &str) -> Vec<UserLogEvent> {
+    let mut events = Vec::new();
+    let mut lines = text.lines();
+    while let Some(line) = lines.next() {
+        let Some((code, cluster_id, proc_id, time, title)) = parse_header(line) else {
+            continue;
+        };
+        let mut details = Vec::new();
+        for detail in lines.by_ref() {
+            if detail.trim() == "..." {
+                break;
+            }
+            let detail = detail.trim();
+            if !detail.is_empty() {
+                details.push(detail.to_string());
+            }
+        }
+        let kind = classify(code, &title, &details);
+        events.push(UserLogEvent {
+            code,
+            cluster_id,
+            proc_id,
+            time,
+            title,
+            details,
+            kind,
+        });
+    }
+    events
+}
+
+// `NNN (cluster.proc.subproc) <date> <time> <title>`
+fn parse_header(line: &str) -> Option<(u32, i64, i64, PrimitiveDateTime, String)> {
+    let (code, rest) = line.split_once(' ')?;
+    if code.len() != 3 {
+        return None;
+    }
+    let code: u32 = code.parse().ok()?;
+    let rest = rest.trim_start().strip_prefix('(')?;
+    let (id, rest) = rest.split_once(')')?;
+    let mut id_parts = id.split('.');
+    let cluster_id: i64 = id_parts.next()?.parse().ok()?;
+    let proc_id: i64 = id_parts.next()?.parse().ok()?;
+
+    let mut parts = rest.trim_start().splitn(3, ' ');
+    let date = parts.next()?;
+    let clock = parts.next()?;
+    let title = parts.next().unwrap_or("").trim().to_string();
+    let time = parse_timestamp(date, clock)?;
+    Some((code, cluster_id, proc_id, time, title))
+}
+
+// Dates are `YYYY-MM-DD` (ISO, the default since 8.x) or `MM/DD` without a year in older logs
+fn parse_timestamp(date: &str, clock: &str) -> Option<PrimitiveDateTime> {
+    let (year, month, day): (i32, u8, u8) = if let Some((m, d)) = date.split_once('/') {
+        (
+            OffsetDateTime::now_utc().year(),
+            m.parse().ok()?,
+            d.parse().ok()?,
+        )
+    } else {
+        let mut it = date.split('-');
+        (
+            it.next()?.parse().ok()?,
+            it.next()?.parse().ok()?,
+            it.next()?.parse().ok()?,
+        )
+    };
+    let date = Date::from_calendar_date(year, Month::try_from(month).ok()?, day).ok()?;
+    let mut hms = clock.split(':');
+    let h: u8 = hms.next()?.parse().ok()?;
+    let m: u8 = hms.next()?.parse().ok()?;
+    // Seconds may carry a fractional part or a timezone offset
+    let s: u8 = hms
+        .next()?
+        .chars()
+        .take_while(|c| c.is_ascii_digit())
+        .collect::<String>()
+        .parse()
+        .ok()?;
+    Some(PrimitiveDateTime::new(date, Time::from_hms(h, m, s).ok()?))
+}
+
+fn classify(code: u32, title: &str, details: &[String]) -> EventKind {
+    match code {
+        0 => EventKind::Submit,
+        1 => EventKind::Execute {
+            host: title
+                .split_once("host:")
+                .map(|(_, h)| h.trim().to_string())
+                .filter(|h| !h.is_empty()),
+        },
+        2 => EventKind::ExecutableError,
+        4 => EventKind::Evicted,
+        5 => EventKind::Terminated(details.iter().find_map(|d| parse_termination(d))),
+        6 => {
+            let image_kb = title.rsplit(':').next().and_then(|v| v.trim().parse().ok());
+            EventKind::ImageSize {
+                image_kb,
+                memory_mb: detail_value(details, "MemoryUsage"),
+                rss_kb: detail_value(details, "ResidentSetSize"),
+            }
+        }
+        9 => EventKind::Aborted {
+            reason: details.first().cloned(),
+        },
+        10 => EventKind::Suspended,
+        11 => EventKind::Unsuspended,
+        12 => {
+            let (code, subcode) = details
+                .iter()
+                .find_map(|d| parse_hold_codes(d))
+                .unwrap_or((None, None));
+            EventKind::Held {
+                reason: details
+                    .iter()
+                    .find(|d| parse_hold_codes(d).is_none())
+                    .cloned(),
+                code,
+                subcode,
+            }
+        }
+        13 => EventKind::Released {
+            reason: details.first().cloned(),
+        },
+        40 => EventKind::FileTransfer {
+            description: title.to_string(),
+        },
+        other => EventKind::Other(other),
+    }
+}
+
+// `(1) Normal termination (return value 0)` / `(0) Abnormal termination (signal 9)`
+fn parse_termination(detail: &str) -> Option<Termination> {
+    let number = |marker: &str| -> Option<i64> {
+        let rest = &detail[detail.find(marker)? + marker.len()..];
+        rest.trim()
+            .trim_end_matches(')')
+            .split_whitespace()
+            .next()?
+            .parse()
+            .ok()
+    };
+    if detail.contains("Normal termination") {
+        number("return value").map(Termination::Exit)
+    } else if detail.contains("Abnormal termination") {
+        number("signal").map(Termination::Signal)
+    } else {
+        None
+    }
+}
+
+// `Code 34 Subcode 0`
+fn parse_hold_codes(detail: &str) -> Option<(Option<i64>, Option<i64>)> {
+    let rest = detail.strip_prefix("Code ")?;
+    let mut words = rest.split_whitespace();
+    let code = words.next().and_then(|w| w.parse().ok());
+    let subcode = match (words.next(), words.next()) {
+        (Some("Subcode"), Some(v)) => v.parse().ok(),
+        _ => None,
+    };
+    Some((code, subcode))
+}
+
+// Image size details look like `1234  -  MemoryUsage of job (MB)`
+fn detail_value(details: &[String], name: &str) -> Option<i64> {
+    details
+        .iter()
+        .find(|d| d.contains(name))
+        .and_then(|d| d.split_whitespace().next())
+        .and_then(|v| v.parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOG: &str = "\
+000 (16337546.000.000) 2025-08-08 15:03:36 Job submitted from host: <10.0.0.1:9618?addrs=10.0.0.1-9618>
+...
+040 (16337546.000.000) 2025-08-08 15:03:40 Started transferring input files
+\tTransferring to host: <10.0.2.136:9618>
+...
+001 (16337546.000.000) 2025-08-08 15:03:41 Job executing on host: <10.0.2.136:9618?addrs=10.0.2.136-9618>
+...
+006 (16337546.000.000) 2025-08-08 15:08:45 Image size of job updated: 2500000
+\t2442  -  MemoryUsage of job (MB)
+\t2500000  -  ResidentSetSize of job (KB)
+...
+012 (16337546.000.000) 2025-08-08 16:00:00 Job was held.
+\tJob has gone over cgroup memory limit of 64000 megabytes.
+\tCode 34 Subcode 0
+...
+013 (16337546.000.000) 2025-08-08 16:05:00 Job was released.
+\tvia condor_release (by user barmstrong)
+...
+005 (16337546.000.000) 2025-08-08 19:30:01 Job terminated.
+\t(0) Abnormal termination (signal 9)
+\t(0) No core file
+...
+";
+
+    #[test]
+    fn parses_events_in_order() {
+        let events = parse_user_log(LOG);
+        let codes: Vec<u32> = events.iter().map(|e| e.code).collect();
+        assert_eq!(codes, vec![0, 40, 1, 6, 12, 13, 5]);
+        assert_eq!(events[0].cluster_id, 16337546);
+        assert_eq!(
+            events[6].timestamp() - events[0].timestamp(),
+            4 * 3600 + 26 * 60 + 25
+        );
+        assert_eq!(
+            events[2].kind,
+            EventKind::Execute {
+                host: Some("<10.0.2.136:9618?addrs=10.0.2.136-9618>".into())
+            }
+        );
+    }
+
+    #[test]
+    fn extracts_event_details() {
+        let events = parse_user_log(LOG);
+        assert_eq!(
+            events[3].kind,
+            EventKind::ImageSize {
+                image_kb: Some(2500000),
+                memory_mb: Some(2442),
+                rss_kb: Some(2500000)
+            }
+        );
+        assert_eq!(
+            events[4].kind,
+            EventKind::Held {
+                reason: Some("Job has gone over cgroup memory limit of 64000 megabytes.".into()),
+                code: Some(34),
+                subcode: Some(0)
+            }
+        );
+        assert_eq!(
+            events[6].kind,
+            EventKind::Terminated(Some(Termination::Signal(9)))
+        );
+        assert_eq!(
+            parse_termination("(1) Normal termination (return value 1)"),
+            Some(Termination::Exit(1))
+        );
+    }
+
+    #[test]
+    fn accepts_dates_without_year() {
+        let events =
+            parse_user_log("001 (1.2.000) 08/08 15:03:41 Job executing on host: <x>\n...\n");
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].proc_id, 2);
+        assert_eq!(events[0].time.hour(), 15);
+    }
+}